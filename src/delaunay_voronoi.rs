@@ -1,190 +1,791 @@
-use cgmath::Point2;
+use std::{cmp::Ordering, collections::HashSet};
+
+use cgmath::{BaseFloat, Point2};
 
 use crate::{
     geometry::ccw,
-    geometry::{ccw_or_linear, in_circle},
+    geometry::{ccw_or_linear, circumcenter, in_circle, lexicographic_order, Scalar},
     mesh::{
-        quad::{FaceEntity, PrimalDEdgeEntity},
-        Mesh,
+        quad::{DualDEdgeEntity, FaceEntity, PrimalDEdgeEntity, VertexEntity},
+        Mesh, MeshCursor, MeshError, MeshExtension,
     },
 };
 
-pub type GeometricVertex = Point2<f32>;
+pub type GeometricVertex<S = f32> = Point2<S>;
 
-#[derive(Debug, PartialEq)]
-pub enum VoronoiVertex {
+/// The payload of a Delaunay face: the circumcenter of its triangle, which is a vertex of the
+/// Voronoi diagram, or nothing for the face outside the hull.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VoronoiVertex<S = f32> {
     Infinite,
-    Finite(f32, f32),
+    Finite(S, S),
 }
 
-impl Default for VoronoiVertex {
+impl<S> Default for VoronoiVertex<S> {
     fn default() -> Self {
         VoronoiVertex::Infinite
     }
 }
 
-impl VoronoiVertex {
+impl<S: Copy> VoronoiVertex<S> {
     pub fn is_infinite(&self) -> bool {
-        VoronoiVertex::Infinite == *self
+        matches!(self, VoronoiVertex::Infinite)
+    }
+    pub fn position(&self) -> Option<Point2<S>> {
+        match *self {
+            VoronoiVertex::Infinite => None,
+            VoronoiVertex::Finite(x, y) => Some(Point2::new(x, y)),
+        }
+    }
+}
+
+impl<S: BaseFloat> VoronoiVertex<S> {
+    /// The payload of a new triangle until its circumcenter is computed.
+    pub fn placeholder() -> Self {
+        VoronoiVertex::Finite(S::zero(), S::zero())
     }
 }
 
+/// Where the last point location ended, to start the next walk from, and the edges whose faces
+/// need their Voronoi vertex recomputed by [`DelaunayMesh::update_voronoi_vertices`].
+///
+/// Swaps, deletions, [`Mesh::revert`] and [`Mesh::compact`] recompute them before returning.
 #[derive(Default)]
-pub struct LocatePointCache {
+pub struct DelaunayCache {
     last_found_point: Option<PrimalDEdgeEntity>,
+    stale_edges: Vec<PrimalDEdgeEntity>,
+    all_stale: bool,
+}
+
+impl<S: Scalar> MeshExtension<GeometricVertex<S>, VoronoiVertex<S::Real>, ()> for DelaunayCache {
+    fn edge_created(&mut self, e: PrimalDEdgeEntity) {
+        self.stale_edges.push(e);
+    }
+    fn edge_deleted(&mut self, e: PrimalDEdgeEntity) {
+        if self.last_found_point.map(|last| last.edge()) == Some(e.edge()) {
+            self.last_found_point = None;
+        }
+    }
+    fn spliced(&mut self, a: PrimalDEdgeEntity, b: PrimalDEdgeEntity) {
+        self.stale_edges.extend([a, b]);
+    }
+    fn swapped(&mut self, e: PrimalDEdgeEntity) {
+        self.stale_edges.push(e);
+    }
+    /// The face labelling `e.rot()` now holds whatever it was moved from.
+    fn face_relabelled(&mut self, e: DualDEdgeEntity) {
+        self.stale_edges.push(e.rot());
+    }
+    /// Nothing known can be trusted, so every face is recomputed on the next update.
+    fn reset(&mut self) {
+        *self = DelaunayCache {
+            all_stale: true,
+            ..Default::default()
+        };
+    }
+    fn settle(mesh: &mut DelaunayMesh<S>) {
+        mesh.update_voronoi_vertices();
+    }
 }
 
-pub type DelaunayMesh = Mesh<GeometricVertex, VoronoiVertex, LocatePointCache>;
+/// A Delaunay triangulation with `S` coordinates, `f32` unless given. Integer coordinates keep
+/// the triangulation exact, with the Voronoi vertices in `f64`.
+pub type DelaunayMesh<S = f32> =
+    Mesh<GeometricVertex<S>, VoronoiVertex<<S as Scalar>::Real>, (), DelaunayCache>;
 
-impl DelaunayMesh {
+impl<S: Scalar> DelaunayMesh<S> {
+    /// Hull edges border the infinite face and are always Delaunay.
     pub fn is_delaunay(&self, xy: PrimalDEdgeEntity) -> bool {
         let xy = self.primal(xy);
-        let a = xy.onext().dest().borrow().clone();
-        let x = xy.org().borrow().clone();
-        let y = xy.dest().borrow().clone();
-        let b = xy.oprev().dest().borrow().clone();
+        if xy.left().is_infinite() || xy.sym().left().is_infinite() {
+            return true;
+        }
+        let a = *xy.onext().dest();
+        let x = *xy.org();
+        let y = *xy.dest();
+        let b = *xy.oprev().dest();
 
         !in_circle(a, x, y, b)
     }
-    /// Finds a dedge `e` such that given point `x` either lies on `e` or is strictly inside the left face of `e`.
-    pub fn locate_point(&mut self, x: GeometricVertex) -> PrimalDEdgeEntity {
-        if self.cache.last_found_point.is_none() {
-            self.cache.last_found_point = self
-                .primal_dedges
-                .iter()
-                .enumerate()
-                .find(|(_, e)| e.is_some())
-                .map(|(i, _)| PrimalDEdgeEntity(i));
-        }
-        let mut e = self.primal(self.cache.last_found_point.unwrap());
-        loop {
-            if x == *e.org().borrow() || x == *e.dest().borrow() {
-                break e.id();
-            } else if !ccw_or_linear(x, *e.org().borrow(), *e.dest().borrow()) {
-                // rightof x, e
-                e.sym_mut();
-                continue;
-            } else if e.left().borrow().is_infinite() {
-                break e.id();
-            } else if ccw(x, *e.onext().org().borrow(), *e.onext().dest().borrow()) {
-                // leftof x, e.onext
-                e.onext_mut();
-                continue;
-            } else if ccw(x, *e.dprev().org().borrow(), *e.dprev().dest().borrow()) {
-                // leftof x, e.dprev
-                e.dprev_mut();
+    /// Store the circumcenter of every finite triangle touched since the last update in its face,
+    /// so the dual of the mesh is its Voronoi diagram.
+    ///
+    /// Edits are tracked through [`DelaunayCache`]: created, deleted, spliced and swapped edges
+    /// mark the faces either side of them, as does a face label moved with
+    /// [`Mesh::set_dual_org`], and after [`Mesh::revert`] or [`Mesh::compact`]
+    /// every face is recomputed. Moving a vertex through [`Mesh::get_vertex_mut`] is not
+    /// tracked; [`DelaunayMesh::update_all_voronoi_vertices`] covers that. Insertion updates
+    /// after every step and the edits that call [`MeshExtension::settle`] before returning,
+    /// other edits need this called after them.
+    pub fn update_voronoi_vertices(&mut self) {
+        if std::mem::take(&mut self.cache.all_stale) {
+            return self.update_all_voronoi_vertices();
+        }
+        let stale = std::mem::take(&mut self.cache.stale_edges);
+        let mut done = HashSet::new();
+        for e in stale {
+            if self.try_get_primal(e).is_err() {
                 continue;
-            } else {
-                break e.id();
             }
+            // a label on a triangle and on a face still being fanned goes to the triangle
+            for d in [e, e.sym()] {
+                let face = self.get_dual(d.rot_inv()).org;
+                if !done.contains(&face) && self.update_voronoi_vertex(d) {
+                    done.insert(face);
+                }
+            }
+        }
+    }
+    /// Recompute the Voronoi vertex of every face.
+    pub fn update_all_voronoi_vertices(&mut self) {
+        self.cache.stale_edges.clear();
+        self.cache.all_stale = false;
+        let faces = self
+            .face_boundaries()
+            .filter_map(|(_, boundary)| boundary.first().copied())
+            .collect::<Vec<_>>();
+        for e in faces {
+            self.update_voronoi_vertex(e);
         }
     }
-    pub fn insert_delaunay_vertex(&mut self, v: GeometricVertex) {
-        let e = self.locate_point(v);
-        if self.primal(e).left().borrow().is_infinite() {
-            self.insert_delaunay_exterior_vertex(v, e);
+    /// Store the circumcenter of the left face of `e`. A finite face that isn't a triangle, like
+    /// the one being fanned in the middle of an insertion, gets
+    /// [`VoronoiVertex::placeholder`] rather than keeping a stale circumcenter. Returns whether
+    /// the face is up to date, so false for the placeholder.
+    fn update_voronoi_vertex(&mut self, e: PrimalDEdgeEntity) -> bool {
+        let face = self.get_dual(e.rot_inv()).org;
+        if self.get_face(face).is_infinite() {
+            return true;
+        }
+        let corners = self
+            .get_lnext_ring(e)
+            .map(|d| *self.primal(d).org())
+            .collect::<Vec<_>>();
+        if let [a, b, c] = corners[..] {
+            // relative to `a`, so integer coordinates only round once
+            let offset = |p: Point2<S>| Point2::new(p.x.real_diff(a.x), p.y.real_diff(a.y));
+            let center = circumcenter(offset(a), offset(b), offset(c));
+            *self.get_face_mut(face) =
+                VoronoiVertex::Finite(a.x.to_real() + center.x, a.y.to_real() + center.y);
+            true
         } else {
-            self.insert_delaunay_interior_vertex(v, e);
-        }
-    }
-    fn insert_delaunay_exterior_vertex(&mut self, v: GeometricVertex, e: PrimalDEdgeEntity) {
-        println!("inserting exterior");
-        let mut boundary_edge = self.primal(e);
-        // find fan start
-        while ccw(
-            v,
-            *boundary_edge.org().borrow(),
-            *boundary_edge.dest().borrow(),
-        ) {
-            boundary_edge.lnext_mut();
-        }
-        let boundary_edge = boundary_edge.id();
-        // insert dangling
-        let new_vertex = self.insert_vertex(v);
-        let dangling_edge = self.connect_vertex(boundary_edge.sym(), new_vertex);
-
-        let mut active_edge = self.primal(dangling_edge.sym()).rprev().id();
-        let fan_start = dangling_edge.sym();
-        // complete fan
-        while !ccw(
-            v,
-            *self.primal(active_edge).org().borrow(),
-            *self.primal(active_edge).dest().borrow(),
-        ) {
-            let e_rnext_id = self.primal(active_edge).rnext().id();
-            let e_id = active_edge.clone();
-            let e_rprev_id = self.primal(active_edge).rprev().id();
-            let new_face = self.insert_face(VoronoiVertex::Finite(0.0, 0.0));
-            let new_edge = self.connect_primal(e_rprev_id.sym(), fan_start);
-            self.get_dual(e_id.rot()).borrow_mut().org = new_face;
-            self.get_dual(e_rnext_id.rot()).borrow_mut().org = new_face;
-            self.get_dual(new_edge.rot()).borrow_mut().org = new_face;
-
-            active_edge = e_rprev_id;
-        }
-        active_edge = fan_start;
-        println!("active_edge: {}", active_edge.0);
-        dbg!(active_edge);
-        // walk down the fan. check for invalid edges
-        loop {
-            println!("looping");
-            let rprev = self.primal(active_edge).rprev().id();
-            dbg!(rprev.0);
-            if !self.is_delaunay(rprev) {
-                self.swap_primal(rprev);
+            *self.get_face_mut(face) = VoronoiVertex::placeholder();
+            false
+        }
+    }
+    /// Finds a dedge `e` such that given point `x` either lies on `e` or is strictly inside the left face of `e`.
+    pub fn locate_point(&mut self, x: GeometricVertex<S>) -> PrimalDEdgeEntity {
+        self.try_locate_point(x)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+    /// Fallible [`DelaunayMesh::locate_point`]. Fails with [`MeshError::EmptyMesh`] if the mesh has no edges.
+    pub fn try_locate_point(
+        &mut self,
+        x: GeometricVertex<S>,
+    ) -> Result<PrimalDEdgeEntity, MeshError> {
+        let mut e = self.locate_start()?;
+        while let Ok(next) = self.locate_step(x, e) {
+            e = next;
+        }
+        self.cache.last_found_point = Some(e);
+        Ok(e)
+    }
+    /// Insert `v` and restore the Delaunay property, see [`DelaunayMesh::insertion`]. Returns
+    /// the new vertex, or the existing vertex at `v`, which is left as it is.
+    pub fn insert_delaunay_vertex(&mut self, v: GeometricVertex<S>) -> VertexEntity {
+        let mut insertion = self.insertion(v);
+        while insertion.step(self).is_some() {}
+        insertion
+            .vertex()
+            .expect("a finished insertion has a vertex")
+    }
+    /// Start inserting `v`, to be carried out one [`DelaunayInsertion::step`] at a time.
+    ///
+    /// Any mesh built by insertion or [`DelaunayMesh::from_points`] can be inserted into,
+    /// starting from an empty one:
+    /// - a point equal to a vertex is merged with it;
+    /// - a point on an edge splits the edge, and the triangles either side of it in two;
+    /// - while every point is on one line the mesh is a chain of edges with the infinite face
+    ///   on both sides, which is fanned into triangles by the first point off the line.
+    ///
+    /// A mesh with no edges is taken to be its first vertex, if it has any.
+    pub fn insertion(&mut self, v: GeometricVertex<S>) -> DelaunayInsertion<S> {
+        DelaunayInsertion {
+            point: v,
+            vertex: None,
+            state: match self.locate_start() {
+                Ok(e) => InsertionState::Locate(e),
+                Err(_) => InsertionState::Start,
+            },
+        }
+    }
+    /// Where point location starts walking: the last edge found, or any edge at all.
+    fn locate_start(&mut self) -> Result<PrimalDEdgeEntity, MeshError> {
+        if self.cache.last_found_point.is_none() {
+            let first = self.primal_dedges().next();
+            self.cache.last_found_point = first;
+        }
+        self.cache.last_found_point.ok_or(MeshError::EmptyMesh)
+    }
+    /// One step of the walk towards `x` from `e`: `Ok` with the next dedge to try, or `Err` with
+    /// `e` itself once `x` lies on `e` or in its left face.
+    fn locate_step(
+        &self,
+        x: GeometricVertex<S>,
+        e: PrimalDEdgeEntity,
+    ) -> Result<PrimalDEdgeEntity, PrimalDEdgeEntity> {
+        let e = self.primal(e);
+        if x == *e.org() || x == *e.dest() {
+            Err(e.id())
+        } else if !ccw_or_linear(x, *e.org(), *e.dest()) {
+            // rightof x, e
+            Ok(e.sym().id())
+        } else if e.left().is_infinite() {
+            Err(e.id())
+        } else if ccw(x, *e.onext().org(), *e.onext().dest()) {
+            // leftof x, e.onext
+            Ok(e.onext().id())
+        } else if ccw(x, *e.dprev().org(), *e.dprev().dest()) {
+            // leftof x, e.dprev
+            Ok(e.dprev().id())
+        } else {
+            Err(e.id())
+        }
+    }
+}
+
+/// Bulk construction.
+impl<S: Scalar> DelaunayMesh<S> {
+    /// Triangulate `points` with the divide-and-conquer algorithm of Guibas and Stolfi, in
+    /// O(n log n). Vertices are inserted in the order of the points, with repeated points
+    /// inserted once.
+    ///
+    /// The outside of the hull is a single [`VoronoiVertex::Infinite`] face, and each triangle
+    /// gets its own face holding its circumcenter. Collinear points give a path with the
+    /// infinite face on both sides.
+    pub fn from_points(points: impl IntoIterator<Item = GeometricVertex<S>>) -> Self {
+        let mut mesh = Self::new();
+        // the first of each run of equal points once sorted, back in their given order
+        let mut points = points.into_iter().enumerate().collect::<Vec<_>>();
+        points.sort_by(|(i, p), (j, q)| lexicographic_order(*p, *q).then(i.cmp(j)));
+        points.dedup_by(|(_, p), (_, q)| p == q);
+        points.sort_by_key(|(i, _)| *i);
+        let mut sites = points
+            .into_iter()
+            .map(|(_, p)| mesh.insert_vertex(p))
+            .collect::<Vec<_>>();
+        let infinity = mesh.insert_face(VoronoiVertex::Infinite);
+        if sites.len() < 2 {
+            return mesh;
+        }
+        sites.sort_by(|&a, &b| lexicographic_order(*mesh.get_vertex(a), *mesh.get_vertex(b)));
+
+        // every edge is made with the infinite face either side, the triangles are labelled
+        // once the topology is done
+        let (hull, _) = mesh.divide_and_conquer(&sites, infinity);
+        let mut labelled = HashSet::new();
+        labelled.extend(mesh.get_lnext_ring(hull.sym()));
+        for e in mesh.primal_dedges().collect::<Vec<_>>() {
+            if labelled.contains(&e) {
                 continue;
             }
-            active_edge = self.primal(active_edge).oprev().id();
-            if (active_edge.0 == fan_start.0)
-                //|| !self.primal(rprev).sym().left().borrow().is_infinite()
-            {
-                break;
+            let face = mesh.insert_face(VoronoiVertex::placeholder());
+            for d in mesh.get_lnext_ring(e).collect::<Vec<_>>() {
+                mesh.set_dual_org(d.rot_inv(), face);
+                labelled.insert(d);
             }
         }
-        println!("done");
+        mesh.update_all_voronoi_vertices();
+        mesh
     }
-    fn insert_delaunay_interior_vertex(&mut self, v: GeometricVertex, e: PrimalDEdgeEntity) {
-        println!("inserting interior");
-        // insert dangling
-        let new_vertex = self.insert_vertex(v);
-        let dangling_edge = self.connect_vertex(e.sym(), new_vertex);
+    /// Triangulate `sites`, sorted by `x` then `y`. Returns the counterclockwise hull edge out of
+    /// the leftmost site and the clockwise hull edge out of the rightmost one.
+    fn divide_and_conquer(
+        &mut self,
+        sites: &[VertexEntity],
+        infinity: FaceEntity,
+    ) -> (PrimalDEdgeEntity, PrimalDEdgeEntity) {
+        let position = |mesh: &Self, i: usize| *mesh.get_vertex(sites[i]);
+        match sites.len() {
+            2 => {
+                let a = self.make_edge(sites[0], sites[1], infinity, infinity);
+                (a, a.sym())
+            }
+            3 => {
+                let a = self.make_edge(sites[0], sites[1], infinity, infinity);
+                let b = self.make_edge(sites[1], sites[2], infinity, infinity);
+                self.splice_primal(a.sym(), b);
+                let (s1, s2, s3) = (position(self, 0), position(self, 1), position(self, 2));
+                if ccw(s1, s2, s3) {
+                    self.connect_primal(b, a);
+                    (a, b.sym())
+                } else if ccw(s1, s3, s2) {
+                    let c = self.connect_primal(b, a);
+                    (c.sym(), c)
+                } else {
+                    // collinear
+                    (a, b.sym())
+                }
+            }
+            n => {
+                let (mut ldo, mut ldi) = self.divide_and_conquer(&sites[..n / 2], infinity);
+                let (mut rdi, mut rdo) = self.divide_and_conquer(&sites[n / 2..], infinity);
 
-        // fan about edge
-        let fan_end = dangling_edge.sym();
-        let mut active_id = e;
-        let mut face: FaceEntity = FaceEntity::default();
-        while self.primal(active_id).lnext().id().0 != fan_end.sym().0 {
-            let old_lprev_id = self.primal(active_id).lprev().id(); // last radial out
-            let old_lnext_id = self.primal(active_id).lnext().id();
-            face = self.get_dual(old_lprev_id.rot_inv()).borrow().org;
-            assert!(!self.get_face(face).borrow().is_infinite());
+                // lower common tangent of the two halves
+                loop {
+                    if self.left_of(*self.primal(rdi).org(), ldi) {
+                        ldi = self.primal(ldi).lnext().id();
+                    } else if self.right_of(*self.primal(ldi).org(), rdi) {
+                        rdi = self.primal(rdi).rprev().id();
+                    } else {
+                        break;
+                    }
+                }
+                let mut basel = self.connect_primal(rdi.sym(), ldi);
+                if self.primal(ldi).org() == self.primal(ldo).org() {
+                    ldo = basel.sym();
+                }
+                if self.primal(rdi).org() == self.primal(rdo).org() {
+                    rdo = basel;
+                }
 
-            self.get_dual(active_id.rot_inv()).borrow_mut().org = face;
-            let new_edge = self.connect_primal(active_id, old_lprev_id);
-            self.get_dual(new_edge.rot_inv()).borrow_mut().org = face;
+                // zip the halves together upwards from the tangent
+                loop {
+                    let valid = |mesh: &Self, e: PrimalDEdgeEntity| {
+                        mesh.right_of(*mesh.primal(e).dest(), basel)
+                    };
+                    // whether the destination of `next` is inside the circle through `basel`
+                    // and the destination of `candidate`. `basel` itself lies on the circle,
+                    // which rounding can't be trusted to say
+                    let beaten = |mesh: &Self, candidate, next: PrimalDEdgeEntity| {
+                        if next.edge() == basel.edge() {
+                            return false;
+                        }
+                        let (base, c, n) =
+                            (mesh.primal(basel), mesh.primal(candidate), mesh.primal(next));
+                        in_circle(*base.dest(), *base.org(), *c.dest(), *n.dest())
+                    };
+                    let mut lcand = self.primal(basel.sym()).onext().id();
+                    if valid(self, lcand) {
+                        while beaten(self, lcand, self.primal(lcand).onext().id()) {
+                            let t = self.primal(lcand).onext().id();
+                            self.delete_primal(lcand);
+                            lcand = t;
+                        }
+                    }
+                    let mut rcand = self.primal(basel).oprev().id();
+                    if valid(self, rcand) {
+                        while beaten(self, rcand, self.primal(rcand).oprev().id()) {
+                            let t = self.primal(rcand).oprev().id();
+                            self.delete_primal(rcand);
+                            rcand = t;
+                        }
+                    }
+                    let (l_valid, r_valid) = (valid(self, lcand), valid(self, rcand));
+                    if !l_valid && !r_valid {
+                        break;
+                    }
+                    let (l, r) = (self.primal(lcand), self.primal(rcand));
+                    basel = if !l_valid
+                        || (r_valid && in_circle(*l.dest(), *l.org(), *r.org(), *r.dest()))
+                    {
+                        self.connect_primal(rcand, basel.sym())
+                    } else {
+                        self.connect_primal(basel.sym(), lcand.sym())
+                    };
+                }
+                (ldo, rdo)
+            }
+        }
+    }
+    fn left_of(&self, x: GeometricVertex<S>, e: PrimalDEdgeEntity) -> bool {
+        let e = self.primal(e);
+        ccw(x, *e.org(), *e.dest())
+    }
+    fn right_of(&self, x: GeometricVertex<S>, e: PrimalDEdgeEntity) -> bool {
+        let e = self.primal(e);
+        ccw(x, *e.dest(), *e.org())
+    }
+}
 
-            let new_face = self.insert_face(VoronoiVertex::Finite(0.0, 0.0));
+/// What a [`DelaunayInsertion::step`] did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertionStep {
+    /// Point location, or the search along the hull or a chain for where to connect the point,
+    /// moved on to this dedge.
+    Walk(PrimalDEdgeEntity),
+    /// The point lies on this dedge or strictly inside its left face, which is infinite when
+    /// the point is outside the hull.
+    EdgeFound(PrimalDEdgeEntity),
+    /// The point is already this vertex. Nothing is inserted and this is the last step.
+    Coincident(VertexEntity),
+    /// The point lay inside this dedge, which now ends at the new vertex. A new edge carries
+    /// on from the new vertex to the old destination.
+    EdgeSplit(PrimalDEdgeEntity),
+    /// An edge joining the new vertex to the rest of the mesh. The first one is dangling.
+    FanEdgeAdded(PrimalDEdgeEntity),
+    /// This edge failed the Delaunay test and was swapped.
+    EdgeFlipped(PrimalDEdgeEntity),
+    /// The vertex is in and the mesh is Delaunay again. This is the last step.
+    Inserted(VertexEntity),
+}
 
-            self.get_dual(new_edge.rot()).borrow_mut().org = new_face;
+/// A vertex insertion in progress, advanced one step at a time by [`DelaunayInsertion::step`].
+/// [`DelaunayMesh::insert_delaunay_vertex`] is this run to completion, so anything following
+/// the steps sees the real algorithm.
+///
+/// The mesh must not be edited by anything else until the insertion is done.
+#[derive(Debug, Clone)]
+pub struct DelaunayInsertion<S = f32> {
+    point: GeometricVertex<S>,
+    vertex: Option<VertexEntity>,
+    state: InsertionState,
+}
 
-            active_id = old_lnext_id;
-        }
-        self.get_dual(fan_end.rot()).borrow_mut().org = face;
+#[derive(Debug, Clone, Copy)]
+enum InsertionState {
+    /// The mesh has no edges yet.
+    Start,
+    Locate(PrimalDEdgeEntity),
+    /// Walking the hull forwards from the located edge past the edges the point can see.
+    FindFanStart(PrimalDEdgeEntity),
+    InsertExterior(PrimalDEdgeEntity),
+    /// Connecting the new vertex to the hull edges it can see, from `active` backwards.
+    CompleteFan {
+        fan_start: PrimalDEdgeEntity,
+        active: PrimalDEdgeEntity,
+    },
+    LegalizeExterior {
+        fan_start: PrimalDEdgeEntity,
+        active: PrimalDEdgeEntity,
+    },
+    InsertInterior(PrimalDEdgeEntity),
+    /// Connecting the new vertex to each corner of the face it landed in.
+    FanAbout {
+        fan_end: PrimalDEdgeEntity,
+        active: PrimalDEdgeEntity,
+    },
+    LegalizeInterior {
+        fan_end: PrimalDEdgeEntity,
+        active: PrimalDEdgeEntity,
+    },
+    LegalizeSpoke(PrimalDEdgeEntity),
+    /// The point lies strictly inside this edge.
+    SplitEdge(PrimalDEdgeEntity),
+    /// Cutting the face left of `side`, which ends at the new vertex, in two if it is finite,
+    /// then the face left of `other_side`.
+    SplitSide {
+        side: PrimalDEdgeEntity,
+        other_side: Option<PrimalDEdgeEntity>,
+    },
+    /// Walking along a chain of collinear edges, on whose line the point lies, towards the
+    /// point.
+    WalkChain(PrimalDEdgeEntity),
+    /// Walking a chain of collinear edges backwards to its first edge. The point is strictly
+    /// left of the chain.
+    FindChainStart(PrimalDEdgeEntity),
+    /// Connecting the new vertex to the end of each edge of the chain in turn. `spoke` runs
+    /// from the new vertex to the start of `active`.
+    FanChain {
+        spoke: PrimalDEdgeEntity,
+        active: PrimalDEdgeEntity,
+    },
+    Finish,
+    Done,
+}
 
-        active_id = fan_end;
+impl<S: Scalar> DelaunayInsertion<S> {
+    pub fn point(&self) -> GeometricVertex<S> {
+        self.point
+    }
+    /// The new vertex once it has been inserted, or the vertex already at the point.
+    pub fn vertex(&self) -> Option<VertexEntity> {
+        self.vertex
+    }
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, InsertionState::Done)
+    }
+    /// Carry out the next step on `mesh`, the mesh the insertion was started on. Returns `None`
+    /// once the insertion is done. The Voronoi vertices of the faces it changed are up to date
+    /// after every step.
+    pub fn step(&mut self, mesh: &mut DelaunayMesh<S>) -> Option<InsertionStep> {
+        let step = self.advance(mesh);
+        mesh.update_voronoi_vertices();
+        step
+    }
+    fn advance(&mut self, mesh: &mut DelaunayMesh<S>) -> Option<InsertionStep> {
+        use InsertionState::*;
+        let v = self.point;
         loop {
-            let suspect_edge = self.primal(active_id).lnext().id();
-            if !self.is_delaunay(suspect_edge) {
-                self.swap_primal(suspect_edge);
-                continue;
-            }
-            active_id = self.primal(active_id).onext().id();
-            if active_id.0 == fan_end.0 {
-                break;
+            match self.state {
+                Start => {
+                    let first = mesh
+                        .vertices
+                        .iter()
+                        .next()
+                        .map(|(index, generation, &p)| (VertexEntity(index, generation), p));
+                    match first {
+                        None => {
+                            self.vertex = Some(mesh.insert_vertex(v));
+                            self.state = Finish;
+                        }
+                        Some((u, p)) if p == v => {
+                            self.vertex = Some(u);
+                            self.state = Done;
+                            return Some(InsertionStep::Coincident(u));
+                        }
+                        Some((u, _)) => {
+                            let infinity = mesh
+                                .faces
+                                .iter()
+                                .find(|(_, _, face)| face.is_infinite())
+                                .map(|(index, generation, _)| FaceEntity(index, generation));
+                            let infinity = infinity
+                                .unwrap_or_else(|| mesh.insert_face(VoronoiVertex::Infinite));
+                            let new_vertex = mesh.insert_vertex(v);
+                            self.vertex = Some(new_vertex);
+                            let e = mesh.make_edge(u, new_vertex, infinity, infinity);
+                            self.state = Finish;
+                            return Some(InsertionStep::FanEdgeAdded(e));
+                        }
+                    }
+                }
+                Locate(e) => {
+                    return Some(match mesh.locate_step(v, e) {
+                        Ok(next) => {
+                            self.state = Locate(next);
+                            InsertionStep::Walk(next)
+                        }
+                        Err(found) => {
+                            mesh.cache.last_found_point = Some(found);
+                            // the walk can stop with the point on a corner of the face
+                            let coincident =
+                                mesh.get_lnext_ring(found).find(|&d| *mesh.primal(d).org() == v);
+                            if let Some(d) = coincident {
+                                let vertex = mesh.get_primal(d).org;
+                                self.vertex = Some(vertex);
+                                self.state = Done;
+                                InsertionStep::Coincident(vertex)
+                            } else {
+                                let e = mesh.primal(found);
+                                self.state = if e.left().is_infinite() && e.right().is_infinite() {
+                                    // every vertex so far is on one line
+                                    if ccw(v, *e.org(), *e.dest()) {
+                                        FindChainStart(found)
+                                    } else {
+                                        WalkChain(found)
+                                    }
+                                } else if e.left().is_infinite() {
+                                    FindFanStart(found)
+                                } else {
+                                    // the point can be on an edge of the face, not inside it
+                                    let mut boundary = mesh.get_lnext_ring(found);
+                                    match boundary.find(|&d| !mesh.left_of(v, d)) {
+                                        Some(d) => SplitEdge(d),
+                                        None => InsertInterior(found),
+                                    }
+                                };
+                                InsertionStep::EdgeFound(found)
+                            }
+                        }
+                    });
+                }
+                FindFanStart(e) => {
+                    let boundary_edge = mesh.primal(e);
+                    let (org, dest) = (*boundary_edge.org(), *boundary_edge.dest());
+                    let side = S::orient2d_sign(v, org, dest);
+                    if side == Ordering::Equal && precedes(org, v) == precedes(v, dest) {
+                        self.state = SplitEdge(e);
+                        continue;
+                    }
+                    // an edge in line with the point can be walked past, towards the point
+                    if side == Ordering::Greater
+                        || (side == Ordering::Equal && precedes(org, dest) == precedes(dest, v))
+                    {
+                        let next = boundary_edge.lnext().id();
+                        self.state = FindFanStart(next);
+                        return Some(InsertionStep::Walk(next));
+                    }
+                    self.state = InsertExterior(e);
+                }
+                InsertExterior(boundary_edge) => {
+                    let new_vertex = mesh.insert_vertex(v);
+                    self.vertex = Some(new_vertex);
+                    let dangling_edge = mesh.connect_vertex(boundary_edge.sym(), new_vertex);
+                    self.state = CompleteFan {
+                        fan_start: dangling_edge.sym(),
+                        active: mesh.primal(dangling_edge.sym()).rprev().id(),
+                    };
+                    return Some(InsertionStep::FanEdgeAdded(dangling_edge));
+                }
+                CompleteFan { fan_start, active } => {
+                    let e = mesh.primal(active);
+                    // a hull edge in line with the point would make a flat triangle
+                    if ccw_or_linear(v, *e.org(), *e.dest()) {
+                        self.state = LegalizeExterior { fan_start, active: fan_start };
+                        continue;
+                    }
+                    let e_rnext_id = e.rnext().id();
+                    let e_rprev_id = e.rprev().id();
+                    let new_face = mesh.insert_face(VoronoiVertex::placeholder());
+                    let new_edge = mesh.connect_primal(e_rprev_id.sym(), fan_start);
+                    mesh.set_dual_org(active.rot(), new_face);
+                    mesh.set_dual_org(e_rnext_id.rot(), new_face);
+                    mesh.set_dual_org(new_edge.rot(), new_face);
+
+                    self.state = CompleteFan { fan_start, active: e_rprev_id };
+                    return Some(InsertionStep::FanEdgeAdded(new_edge));
+                }
+                // walk down the fan, checking the edge opposite each spoke
+                LegalizeExterior { fan_start, active } => {
+                    let rprev = mesh.primal(active).rprev().id();
+                    if !mesh.is_delaunay(rprev) {
+                        mesh.swap_primal(rprev);
+                        return Some(InsertionStep::EdgeFlipped(rprev));
+                    }
+                    let active = mesh.primal(active).oprev().id();
+                    self.state = if active.0 == fan_start.0 {
+                        Finish
+                    } else {
+                        LegalizeExterior { fan_start, active }
+                    };
+                }
+                InsertInterior(e) => {
+                    let new_vertex = mesh.insert_vertex(v);
+                    self.vertex = Some(new_vertex);
+                    let dangling_edge = mesh.connect_vertex(e.sym(), new_vertex);
+                    self.state = FanAbout { fan_end: dangling_edge.sym(), active: e };
+                    return Some(InsertionStep::FanEdgeAdded(dangling_edge));
+                }
+                FanAbout { fan_end, active } => {
+                    // the last triangle is whatever is left of the old face
+                    if mesh.primal(active).lnext().id().0 == fan_end.sym().0 {
+                        self.state = LegalizeInterior { fan_end, active: fan_end };
+                        continue;
+                    }
+                    let last_radial_out = mesh.primal(active).lprev().id();
+                    let old_lnext_id = mesh.primal(active).lnext().id();
+                    assert!(!mesh.primal(active).left().is_infinite());
+
+                    // the triangle cut off keeps the face, the rest of it gets a new one
+                    let new_edge = mesh.connect_primal(active, last_radial_out);
+                    let new_face = mesh.insert_face(VoronoiVertex::placeholder());
+                    for d in mesh.get_lnext_ring(new_edge.sym()).collect::<Vec<_>>() {
+                        mesh.set_dual_org(d.rot_inv(), new_face);
+                    }
+
+                    self.state = FanAbout { fan_end, active: old_lnext_id };
+                    return Some(InsertionStep::FanEdgeAdded(new_edge));
+                }
+                // turn around the new vertex, checking the edge opposite each spoke
+                LegalizeInterior { fan_end, active } => {
+                    let suspect_edge = mesh.primal(active).lnext().id();
+                    if !mesh.is_delaunay(suspect_edge) {
+                        mesh.swap_primal(suspect_edge);
+                        return Some(InsertionStep::EdgeFlipped(suspect_edge));
+                    }
+                    let active = mesh.primal(active).onext().id();
+                    self.state = if active.0 == fan_end.0 {
+                        LegalizeSpoke(active)
+                    } else {
+                        LegalizeInterior { fan_end, active }
+                    };
+                }
+                LegalizeSpoke(e) => {
+                    self.state = Finish;
+                    if !mesh.is_delaunay(e) {
+                        mesh.swap_primal(e);
+                        return Some(InsertionStep::EdgeFlipped(e));
+                    }
+                }
+                SplitEdge(e) => {
+                    let new_vertex = mesh.insert_vertex(v);
+                    self.vertex = Some(new_vertex);
+                    let rest = mesh.split_edge(e, new_vertex);
+                    self.state = SplitSide { side: e, other_side: Some(rest.sym()) };
+                    return Some(InsertionStep::EdgeSplit(e));
+                }
+                SplitSide { side, other_side } => {
+                    self.state = match other_side {
+                        Some(other_side) => SplitSide { side: other_side, other_side: None },
+                        None => LegalizeInterior { fan_end: side.sym(), active: side.sym() },
+                    };
+                    let face = mesh.get_dual(side.rot_inv()).org;
+                    if !mesh.get_face(face).is_infinite() {
+                        let opposite = mesh.primal(side).lprev().id();
+                        let new_face = mesh.insert_face(VoronoiVertex::placeholder());
+                        let new_edge = mesh.connect_primal(side, opposite);
+                        mesh.set_dual_org(new_edge.rot(), face);
+                        for d in [side, new_edge, opposite] {
+                            mesh.set_dual_org(d.rot_inv(), new_face);
+                        }
+                        return Some(InsertionStep::FanEdgeAdded(new_edge));
+                    }
+                }
+                WalkChain(e) => {
+                    let edge = mesh.primal(e);
+                    let (org, dest) = (*edge.org(), *edge.dest());
+                    if precedes(org, v) == precedes(v, dest) {
+                        self.state = SplitEdge(e);
+                        continue;
+                    }
+                    let next = if precedes(org, dest) != precedes(dest, v) {
+                        // behind the origin
+                        e.sym()
+                    } else if edge.lnext().id() != e.sym() {
+                        edge.lnext().id()
+                    } else {
+                        // past the end of the chain
+                        let new_vertex = mesh.insert_vertex(v);
+                        self.vertex = Some(new_vertex);
+                        let new_edge = mesh.connect_vertex(e, new_vertex);
+                        self.state = Finish;
+                        return Some(InsertionStep::FanEdgeAdded(new_edge));
+                    };
+                    self.state = WalkChain(next);
+                    return Some(InsertionStep::Walk(next));
+                }
+                FindChainStart(e) => {
+                    let prev = mesh.primal(e).lprev().id();
+                    if prev != e.sym() {
+                        self.state = FindChainStart(prev);
+                        return Some(InsertionStep::Walk(prev));
+                    }
+                    let new_vertex = mesh.insert_vertex(v);
+                    self.vertex = Some(new_vertex);
+                    let dangling_edge = mesh.connect_vertex(e.sym(), new_vertex);
+                    self.state = FanChain { spoke: dangling_edge.sym(), active: e };
+                    return Some(InsertionStep::FanEdgeAdded(dangling_edge));
+                }
+                // each triangle of the fan has the infinite face beyond its chain edge, so the
+                // fan is Delaunay as it is
+                FanChain { spoke, active } => {
+                    let next = mesh.primal(active).lnext().id();
+                    let new_face = mesh.insert_face(VoronoiVertex::placeholder());
+                    let new_edge = mesh.connect_primal(active, spoke);
+                    for d in [active, new_edge, spoke] {
+                        mesh.set_dual_org(d.rot_inv(), new_face);
+                    }
+                    self.state = if next == active.sym() {
+                        Finish
+                    } else {
+                        FanChain { spoke: new_edge.sym(), active: next }
+                    };
+                    return Some(InsertionStep::FanEdgeAdded(new_edge));
+                }
+                Finish => {
+                    self.state = Done;
+                    return self.vertex.map(InsertionStep::Inserted);
+                }
+                Done => return None,
             }
         }
-        if !self.is_delaunay(active_id) {
-            self.swap_primal(active_id);
-        }
     }
 }
+
+/// Whether `a` comes before `b` in [`lexicographic_order`]. Along a line this is the order of
+/// the points, so it places collinear points exactly.
+fn precedes<S: Scalar>(a: Point2<S>, b: Point2<S>) -> bool {
+    lexicographic_order(a, b) == Ordering::Less
+}
@@ -0,0 +1,325 @@
+//! Wavefront OBJ and OFF polygon files.
+//!
+//! Files are read into a [`PolygonSoup`] of positions and index loops, which is then turned
+//! into a mesh with [`Mesh::from_polygons`]. Writing goes the other way, one polygon per live
+//! face.
+
+use std::{
+    error::Error,
+    fmt,
+    io::{self, BufRead, Write},
+};
+
+use cgmath::{BaseFloat, Point2, Point3};
+
+use crate::{
+    delaunay_voronoi::VoronoiVertex,
+    mesh::{Mesh, MeshExtension, PolygonError},
+    topological::TopologicalMesh,
+};
+
+/// Vertex payloads that have a position in a polygon file.
+pub trait FilePosition {
+    fn from_xyz(xyz: [f64; 3]) -> Self;
+    fn xyz(&self) -> [f64; 3];
+}
+
+impl FilePosition for Point2<f32> {
+    /// Drops `z`.
+    fn from_xyz([x, y, _]: [f64; 3]) -> Self {
+        Point2::new(x as f32, y as f32)
+    }
+    fn xyz(&self) -> [f64; 3] {
+        [self.x as f64, self.y as f64, 0.0]
+    }
+}
+impl FilePosition for Point2<f64> {
+    /// Drops `z`.
+    fn from_xyz([x, y, _]: [f64; 3]) -> Self {
+        Point2::new(x, y)
+    }
+    fn xyz(&self) -> [f64; 3] {
+        [self.x, self.y, 0.0]
+    }
+}
+impl FilePosition for Point3<f32> {
+    fn from_xyz([x, y, z]: [f64; 3]) -> Self {
+        Point3::new(x as f32, y as f32, z as f32)
+    }
+    fn xyz(&self) -> [f64; 3] {
+        [self.x as f64, self.y as f64, self.z as f64]
+    }
+}
+impl FilePosition for Point3<f64> {
+    fn from_xyz([x, y, z]: [f64; 3]) -> Self {
+        Point3::new(x, y, z)
+    }
+    fn xyz(&self) -> [f64; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+
+/// Face payloads of meshes read from or written to a polygon file.
+pub trait FileFace {
+    /// Payload for polygon `Some(index)` of the file, or for an outer face bounding a hole.
+    fn from_polygon(polygon: Option<usize>) -> Self;
+    /// Whether the face is written out as a polygon.
+    fn is_written(&self) -> bool;
+}
+
+/// Faces read from a file get a placeholder Voronoi vertex until
+/// [`DelaunayMesh::update_voronoi_vertices`] is called.
+///
+/// [`DelaunayMesh::update_voronoi_vertices`]: crate::delaunay_voronoi::DelaunayMesh::update_voronoi_vertices
+impl<S: BaseFloat> FileFace for VoronoiVertex<S> {
+    fn from_polygon(polygon: Option<usize>) -> Self {
+        match polygon {
+            Some(_) => VoronoiVertex::placeholder(),
+            None => VoronoiVertex::Infinite,
+        }
+    }
+    /// Infinite faces are the outside of the triangulation and are not written.
+    fn is_written(&self) -> bool {
+        !self.is_infinite()
+    }
+}
+
+/// The index of the polygon in the file, `None` for outer faces.
+impl FileFace for Option<usize> {
+    fn from_polygon(polygon: Option<usize>) -> Self {
+        polygon
+    }
+    fn is_written(&self) -> bool {
+        self.is_some()
+    }
+}
+
+/// Failure to read a polygon file.
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    /// Line `line` (counting from 1) could not be parsed.
+    Syntax {
+        line: usize,
+        reason: &'static str,
+    },
+    /// The file ended while more elements were expected.
+    UnexpectedEnd(&'static str),
+}
+
+impl From<io::Error> for ReadError {
+    fn from(err: io::Error) -> Self {
+        ReadError::Io(err)
+    }
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Io(err) => write!(f, "{}", err),
+            ReadError::Syntax { line, reason } => write!(f, "line {}: {}", line, reason),
+            ReadError::UnexpectedEnd(reason) => write!(f, "unexpected end of file: {}", reason),
+        }
+    }
+}
+
+impl Error for ReadError {}
+
+/// Positions and polygons as index loops into them, as stored in a polygon file.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PolygonSoup {
+    pub positions: Vec<[f64; 3]>,
+    pub polygons: Vec<Vec<usize>>,
+}
+
+impl PolygonSoup {
+    /// Read the `v` and `f` statements of an OBJ file, ignoring everything else.
+    pub fn read_obj(reader: impl BufRead) -> Result<Self, ReadError> {
+        let mut soup = PolygonSoup::default();
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let syntax = |reason| ReadError::Syntax {
+                line: number + 1,
+                reason,
+            };
+            let mut tokens = strip_comment(&line).split_whitespace();
+            match tokens.next() {
+                Some("v") => {
+                    let xyz = parse_xyz(&mut tokens).ok_or_else(|| syntax("bad vertex"))?;
+                    soup.positions.push(xyz);
+                }
+                Some("f") => {
+                    let polygon = tokens
+                        .map(|token| {
+                            // `v`, `v/vt`, `v//vn` or `v/vt/vn`, 1-based or negative for
+                            // relative to the last vertex
+                            let index = token.split('/').next()?.parse::<isize>().ok()?;
+                            match index {
+                                0 => None,
+                                i if i < 0 => soup.positions.len().checked_sub(i.unsigned_abs()),
+                                i => Some(i as usize - 1),
+                            }
+                        })
+                        .collect::<Option<Vec<_>>>()
+                        .ok_or_else(|| syntax("bad face index"))?;
+                    soup.polygons.push(polygon);
+                }
+                _ => (),
+            }
+        }
+        Ok(soup)
+    }
+
+    /// Read an OFF file. Per-element colors are ignored.
+    pub fn read_off(reader: impl BufRead) -> Result<Self, ReadError> {
+        let mut lines = reader
+            .lines()
+            .enumerate()
+            .map(|(number, line)| line.map(|line| (number + 1, line)))
+            .filter(|line| match line {
+                Ok((_, line)) => !strip_comment(line).trim().is_empty(),
+                Err(_) => true,
+            });
+        let mut next_line = |reason| match lines.next() {
+            Some(line) => Ok(line?),
+            None => Err(ReadError::UnexpectedEnd(reason)),
+        };
+
+        let (mut number, mut line) = next_line("missing OFF header")?;
+        if strip_comment(&line).trim() == "OFF" {
+            (number, line) = next_line("missing element counts")?;
+        }
+        let header = strip_comment(&line).trim().trim_start_matches("OFF");
+        let counts = header
+            .split_whitespace()
+            .take(2)
+            .map(|count| count.parse::<usize>().ok())
+            .collect::<Option<Vec<_>>>()
+            .filter(|counts| counts.len() == 2)
+            .ok_or(ReadError::Syntax {
+                line: number,
+                reason: "bad element counts",
+            })?;
+
+        let mut soup = PolygonSoup::default();
+        for _ in 0..counts[0] {
+            let (number, line) = next_line("missing vertex")?;
+            let xyz = parse_xyz(&mut strip_comment(&line).split_whitespace()).ok_or(
+                ReadError::Syntax {
+                    line: number,
+                    reason: "bad vertex",
+                },
+            )?;
+            soup.positions.push(xyz);
+        }
+        for _ in 0..counts[1] {
+            let (number, line) = next_line("missing face")?;
+            let mut tokens = strip_comment(&line)
+                .split_whitespace()
+                .map(|token| token.parse::<usize>().ok());
+            let polygon = tokens
+                .next()
+                .flatten()
+                .and_then(|len| {
+                    let polygon = tokens.by_ref().take(len).collect::<Option<Vec<_>>>()?;
+                    (polygon.len() == len).then_some(polygon)
+                })
+                .ok_or(ReadError::Syntax {
+                    line: number,
+                    reason: "bad face",
+                })?;
+            soup.polygons.push(polygon);
+        }
+        Ok(soup)
+    }
+
+    pub fn write_obj(&self, mut writer: impl Write) -> io::Result<()> {
+        for [x, y, z] in &self.positions {
+            writeln!(writer, "v {} {} {}", x, y, z)?;
+        }
+        for polygon in &self.polygons {
+            write!(writer, "f")?;
+            for i in polygon {
+                write!(writer, " {}", i + 1)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_off(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "OFF")?;
+        writeln!(writer, "{} {} 0", self.positions.len(), self.polygons.len())?;
+        for [x, y, z] in &self.positions {
+            writeln!(writer, "{} {} {}", x, y, z)?;
+        }
+        for polygon in &self.polygons {
+            write!(writer, "{}", polygon.len())?;
+            for i in polygon {
+                write!(writer, " {}", i)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Build a mesh with positioned vertices. See [`Mesh::from_polygons`].
+    pub fn to_mesh<V: FilePosition, F: FileFace, E: Default, Cache: MeshExtension<V, F, E>>(
+        &self,
+    ) -> Result<Mesh<V, F, E, Cache>, PolygonError> {
+        Mesh::from_polygons(
+            self.positions.iter().map(|&xyz| V::from_xyz(xyz)),
+            &self.polygons,
+            F::from_polygon,
+        )
+    }
+
+    /// Build a mesh of the topology alone, labelling vertices and faces with their index in
+    /// the soup. Outer faces are labelled `None`.
+    pub fn to_topological_mesh(&self) -> Result<TopologicalMesh<Option<usize>>, PolygonError> {
+        Mesh::from_polygons(
+            (0..self.positions.len()).map(Some),
+            &self.polygons,
+            |polygon| polygon,
+        )
+    }
+
+    /// One polygon per live face that [`FileFace::is_written`], from its lnext ring. Live
+    /// vertices are numbered in slot order.
+    pub fn from_mesh<V: FilePosition, F: FileFace, E, Cache: MeshExtension<V, F, E>>(
+        mesh: &Mesh<V, F, E, Cache>,
+    ) -> Self {
+        let mut indices = vec![0; mesh.vertices.slot_count()];
+        let mut soup = PolygonSoup::default();
+        for (i, (slot, _, v)) in mesh.vertices.iter().enumerate() {
+            indices[slot] = i;
+            soup.positions.push(v.xyz());
+        }
+        soup.polygons = mesh
+            .face_boundaries()
+            .filter(|(face, boundary)| !boundary.is_empty() && mesh.get_face(*face).is_written())
+            .map(|(_, boundary)| {
+                boundary
+                    .into_iter()
+                    .map(|e| indices[mesh.get_primal(e).org.0])
+                    .collect()
+            })
+            .collect();
+        soup
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    line.split('#').next().unwrap_or_default()
+}
+
+/// `x y [z]`, `z` defaulting to 0 for planar files.
+fn parse_xyz<'a>(tokens: &mut impl Iterator<Item = &'a str>) -> Option<[f64; 3]> {
+    let x = tokens.next()?.parse().ok()?;
+    let y = tokens.next()?.parse().ok()?;
+    let z = match tokens.next() {
+        Some(z) => z.parse().ok()?,
+        None => 0.0,
+    };
+    Some([x, y, z])
+}
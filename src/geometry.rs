@@ -1,5 +1,9 @@
 // use bevy::utils::HashMap;
-use cgmath::{Matrix4, Matrix3, Point2, SquareMatrix};
+use std::{cmp::Ordering, fmt::Debug};
+
+use cgmath::{BaseFloat, Point2};
+
+use crate::predicates::{incircle, incircle_integer, orient2d, orient2d_integer};
 
 // // Quad here indicates /Quad-Tree/ hierachical data structure not /Quad-Edge/.
 // struct Rect<T> {
@@ -24,58 +28,166 @@ use cgmath::{Matrix4, Matrix3, Point2, SquareMatrix};
 //     },
 // }
 
-pub fn ccw(a: Point2<f32>, b: Point2<f32>, c: Point2<f32>) -> bool {
-    let test = Matrix3::new(
-        a.x,
-        b.x,
-        c.x,
-        a.y,
-        b.y,
-        c.y,
-        1.0,
-        1.0,
-        1.0,
-    ).determinant();
-
-    test > 0.0
+/// Coordinate types of the geometric meshes: `f32` or `f64`, or `i32` or `i64` for points on an
+/// integer lattice.
+///
+/// The orientation and incircle signs are exact for all of them, from the adaptive predicates
+/// for floats and from wide integer arithmetic for integers. Points derived from the vertices
+/// that aren't on the lattice, circumcenters and Voronoi cells, are in [`Scalar::Real`].
+pub trait Scalar: Copy + PartialOrd + Debug {
+    /// The float type of derived points: the type itself for floats and `f64` for integers.
+    type Real: BaseFloat;
+
+    /// The nearest `Real`.
+    fn to_real(self) -> Self::Real;
+    /// The nearest `Real` to `self - other`, which is exact for nearby integers however large.
+    fn real_diff(self, other: Self) -> Self::Real;
+    /// The nearest `f64`, which is exact except for `i64` values past 2^53.
+    fn as_f64(self) -> f64;
+    /// `Greater` if `a`, `b` and `c` turn counterclockwise, `Less` if clockwise and `Equal` if
+    /// they are collinear, see [`orient2d`].
+    fn orient2d_sign(a: Point2<Self>, b: Point2<Self>, c: Point2<Self>) -> Ordering;
+    /// `Greater` if `d` is inside the circle through `a`, `b` and `c`, `Less` if outside and
+    /// `Equal` if the four are cocircular, see [`incircle`].
+    fn incircle_sign(
+        a: Point2<Self>,
+        b: Point2<Self>,
+        c: Point2<Self>,
+        d: Point2<Self>,
+    ) -> Ordering;
+}
+
+impl Scalar for f32 {
+    type Real = f32;
+
+    fn to_real(self) -> f32 {
+        self
+    }
+    fn real_diff(self, other: f32) -> f32 {
+        self - other
+    }
+    fn as_f64(self) -> f64 {
+        self.into()
+    }
+    fn orient2d_sign(a: Point2<f32>, b: Point2<f32>, c: Point2<f32>) -> Ordering {
+        sign(orient2d(a, b, c))
+    }
+    fn incircle_sign(a: Point2<f32>, b: Point2<f32>, c: Point2<f32>, d: Point2<f32>) -> Ordering {
+        sign(incircle(a, b, c, d))
+    }
+}
+
+impl Scalar for f64 {
+    type Real = f64;
+
+    fn to_real(self) -> f64 {
+        self
+    }
+    fn real_diff(self, other: f64) -> f64 {
+        self - other
+    }
+    fn as_f64(self) -> f64 {
+        self
+    }
+    fn orient2d_sign(a: Point2<f64>, b: Point2<f64>, c: Point2<f64>) -> Ordering {
+        sign(orient2d(a, b, c))
+    }
+    fn incircle_sign(a: Point2<f64>, b: Point2<f64>, c: Point2<f64>, d: Point2<f64>) -> Ordering {
+        sign(incircle(a, b, c, d))
+    }
+}
+
+impl Scalar for i32 {
+    type Real = f64;
+
+    fn to_real(self) -> f64 {
+        self.into()
+    }
+    fn real_diff(self, other: i32) -> f64 {
+        (i64::from(self) - i64::from(other)) as f64
+    }
+    fn as_f64(self) -> f64 {
+        self.into()
+    }
+    fn orient2d_sign(a: Point2<i32>, b: Point2<i32>, c: Point2<i32>) -> Ordering {
+        orient2d_integer(a.map(i64::from), b.map(i64::from), c.map(i64::from))
+    }
+    fn incircle_sign(a: Point2<i32>, b: Point2<i32>, c: Point2<i32>, d: Point2<i32>) -> Ordering {
+        let [a, b, c, d] = [a, b, c, d].map(|p| p.map(i64::from));
+        incircle_integer(a, b, c, d)
+    }
+}
+
+impl Scalar for i64 {
+    type Real = f64;
+
+    fn to_real(self) -> f64 {
+        self as f64
+    }
+    fn real_diff(self, other: i64) -> f64 {
+        (i128::from(self) - i128::from(other)) as f64
+    }
+    fn as_f64(self) -> f64 {
+        self as f64
+    }
+    fn orient2d_sign(a: Point2<i64>, b: Point2<i64>, c: Point2<i64>) -> Ordering {
+        orient2d_integer(a, b, c)
+    }
+    fn incircle_sign(a: Point2<i64>, b: Point2<i64>, c: Point2<i64>, d: Point2<i64>) -> Ordering {
+        incircle_integer(a, b, c, d)
+    }
 }
-pub fn ccw_or_linear(a: Point2<f32>, b: Point2<f32>, c: Point2<f32>) -> bool {
-    let test = Matrix3::new(
-        a.x,
-        b.x,
-        c.x,
-        a.y,
-        b.y,
-        c.y,
-        1.0,
-        1.0,
-        1.0,
-    ).determinant();
-
-    test >= 0.0
+
+fn sign(det: f64) -> Ordering {
+    det.partial_cmp(&0.0).unwrap_or(Ordering::Equal)
 }
 
+pub fn ccw<S: Scalar>(a: Point2<S>, b: Point2<S>, c: Point2<S>) -> bool {
+    S::orient2d_sign(a, b, c) == Ordering::Greater
+}
+pub fn ccw_or_linear<S: Scalar>(a: Point2<S>, b: Point2<S>, c: Point2<S>) -> bool {
+    S::orient2d_sign(a, b, c) != Ordering::Less
+}
+
+/// Whether `d` is inside the circle through the counterclockwise triangle `abc`.
+///
+/// Four cocircular points are decided by simulation of simplicity: each point is taken to sit
+/// an infinitesimal distance inside the circle through any three others, by more the earlier
+/// it comes in [`lexicographic_order`]. Perturbed like this no four points are cocircular, so
+/// exactly one diagonal of a cocircular quadrilateral is Delaunay and the triangulation of a
+/// point set doesn't depend on the order its points were inserted in.
+pub fn in_circle<S: Scalar>(a: Point2<S>, b: Point2<S>, c: Point2<S>, d: Point2<S>) -> bool {
+    let det = S::incircle_sign(a, b, c, d);
+    if det != Ordering::Equal {
+        return det == Ordering::Greater;
+    }
+    // how moving each point inwards moves the determinant, the sign of its cofactor
+    let mut nudges = [
+        (a, S::orient2d_sign(c, b, d)),
+        (b, S::orient2d_sign(a, c, d)),
+        (c, S::orient2d_sign(b, a, d)),
+        (d, S::orient2d_sign(a, b, c)),
+    ];
+    nudges.sort_by(|(p, _), (q, _)| lexicographic_order(*p, *q));
+    nudges
+        .iter()
+        .find(|(_, effect)| *effect != Ordering::Equal)
+        .is_some_and(|(_, effect)| *effect == Ordering::Greater)
+}
+
+/// Order of points by `x`, then `y`. Coordinates must not be NaN.
+pub fn lexicographic_order<S: Scalar>(a: Point2<S>, b: Point2<S>) -> Ordering {
+    let order = |p: S, q: S| p.partial_cmp(&q).expect("coordinate is NaN");
+    order(a.x, b.x).then_with(|| order(a.y, b.y))
+}
 
-pub fn in_circle(a: Point2<f32>, b: Point2<f32>, c: Point2<f32>, d: Point2<f32>) -> bool {
-    let test = Matrix4::new(
-        a.x,
-        b.x,
-        c.x,
-        d.x,
-        a.y,
-        b.y,
-        c.y,
-        d.y,
-        a.x * a.x + a.y * a.y,
-        b.x * b.x + b.y * b.y,
-        c.x * c.x + c.y * c.y,
-        d.x * d.x + d.y * d.y,
-        1.0,
-        1.0,
-        1.0,
-        1.0,
-    )
-    .determinant();
-
-    test > 0.0
+/// Center of the circle through `a`, `b` and `c`. Not finite if they are collinear.
+pub fn circumcenter<S: BaseFloat>(a: Point2<S>, b: Point2<S>, c: Point2<S>) -> Point2<S> {
+    let two = S::one() + S::one();
+    let (bx, by) = (b.x - a.x, b.y - a.y);
+    let (cx, cy) = (c.x - a.x, c.y - a.y);
+    let d = two * (bx * cy - by * cx);
+    let b2 = bx * bx + by * by;
+    let c2 = cx * cx + cy * cy;
+    Point2::new(a.x + (cy * b2 - by * c2) / d, a.y + (bx * c2 - cx * b2) / d)
 }
@@ -32,6 +32,7 @@ pub struct AnimateMesh;
 impl Plugin for AnimateMesh {
     fn build(&self, app: &mut App) {
         app.init_resource::<ActiveDedge>()
+            .init_resource::<algorithm_animate::ActiveInsertion>()
             .insert_resource(HighlightColors(vec![
                 Color::YELLOW,
                 Color::YELLOW_GREEN,
@@ -57,15 +58,15 @@ impl Plugin for AnimateMesh {
             .add_system_set(
                 // MeshStage::DelaunayMeshRead,
                 SystemSet::on_update(AnimationState::LocatePoint)
-                    .with_system(algorithm_animate::update_animation_locate_point),
+                    .with_system(algorithm_animate::update_animation_insertion),
             )
             .add_system_set(
                 SystemSet::on_update(AnimationState::InsertExterior)
-                    .with_system(algorithm_animate::update_animation_insert_exterior),
+                    .with_system(algorithm_animate::update_animation_insertion),
             )
             .add_system_set(
                 SystemSet::on_update(AnimationState::InsertInterior)
-                    .with_system(algorithm_animate::update_animation_insert_interior),
+                    .with_system(algorithm_animate::update_animation_insertion),
             )
             // .add_system_set(SystemSet::on_enter(AnimationState::Stopped).with_system(debug))
             .add_system_set(
@@ -104,12 +105,9 @@ fn animate_insert_random_vertex(mut animate_events: EventWriter<AnimateMeshEvent
     )));
 }
 
-fn debug(mesh: NonSend<DelaunayMesh>) {
-    for (i, edge) in mesh.primal_dedges.iter().enumerate() {
-        println!("{}, {:?}", i, edge);
-    }
-    for (i, edge) in mesh.dual_dedges.iter().enumerate() {
-        println!("{}, {:?}", i, edge);
+fn debug(mesh: Res<DelaunayMesh>) {
+    for (i, generation, edge) in mesh.quad_edges.iter() {
+        println!("{}@{}, {:?}", i, generation, edge);
     }
 }
 
@@ -178,15 +176,15 @@ fn setup_dedge_highlights(
 
 fn update_highlights_to_follow_mesh(
     mut commands: Commands,
-    mesh: NonSend<DelaunayMesh>,
+    mesh: Res<DelaunayMesh>,
     mut query: Query<(&mut Transform, &mut Visibility, &Highlight)>,
 ) {
     for (mut transform, mut visibility, highlight) in query.iter_mut() {
         if let Some(entity) = highlight.1 {
             let dedge = mesh.primal(entity.into());
-            let org = dedge.org().borrow();
+            let org = dedge.org();
             let org = Vec2::new(org.x, org.y);
-            let dest = dedge.dest().borrow();
+            let dest = dedge.dest();
             let dest = Vec2::new(dest.x, dest.y);
             let r = dest - org;
 
@@ -211,7 +209,7 @@ pub struct ActiveDedge(pub Option<PDEdgeEntity>);
 fn update_arrow_frames(
     selected_dedge: Res<ActiveDedge>,
     arrow_frames: default_arrows::DefaultArrowsParam,
-    mesh: NonSend<DelaunayMesh>,
+    mesh: Res<DelaunayMesh>,
     mut query: Query<(&mut bevy_arrow::Arrow, &PDEdgeEntity)>,
     mut animation_state: ResMut<State<AnimationState>>,
 ) {
@@ -242,7 +240,7 @@ fn update_arrow_frames(
     };
 
     for (mut arrow, dedge) in query.iter_mut() {
-        let is_boundary = mesh.primal((*dedge).into()).left().borrow().is_infinite();
+        let is_boundary = mesh.primal((*dedge).into()).left().is_infinite();
         let is_delaunay = mesh.is_delaunay((*dedge).into());
         let mut color = if is_delaunay {
             red
@@ -277,7 +275,6 @@ pub enum AnimateMeshEvent<'a> {
     SetTargetPosition(Option<&'a str>, Vec2),
     SetTargetVisibility(Option<&'a str>, bool),
     BeginLocateAnimation(Option<&'a str>),
-    BeginInsertExteriorAnimation(Option<&'a str>),
     Done,
 }
 
@@ -344,12 +341,6 @@ fn handle_animation_events(
                 }
                 animation_state.set(AnimationState::LocatePoint).unwrap();
             }
-            AnimateMeshEvent::BeginInsertExteriorAnimation(phase) => {
-                if let Some(phase) = phase {
-                    phase_text.value = format!("{}\n", phase);
-                }
-                animation_state.set(AnimationState::InsertExterior).unwrap();
-            }
             AnimateMeshEvent::SetMarked(action, pde, set) => {
                 if let Some(action) = *action {
                     action_text.value = action.to_string();
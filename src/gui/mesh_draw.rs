@@ -1,9 +1,13 @@
 use bevy::prelude::*;
 use bevy::render::mesh::VertexAttributeValues;
 use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+use bevy::utils::HashSet;
 
-use quad_edge::delaunay_voronoi::DelaunayMesh;
-use quad_edge::mesh::quad::{PrimalDEdgeEntity, VertexEntity};
+use quad_edge::delaunay_voronoi::{DelaunayMesh, GeometricVertex, VoronoiVertex};
+use quad_edge::mesh::quad::{Generation, PrimalDEdgeEntity, VertexEntity};
+use quad_edge::mesh::{History, MeshChange};
+
+use super::animate_mesh::AnimationState;
 
 #[derive(Clone, Hash, Debug, PartialEq, Eq, StageLabel)]
 pub enum MeshStage {
@@ -14,8 +18,8 @@ pub struct MeshDraw;
 impl Plugin for MeshDraw {
     fn build(&self, app: &mut App) {
         app.insert_resource::<f32>(150.0)
+            .init_resource::<MeshHistory>()
             .add_event::<MeshEvent>()
-            .add_event::<NotifyMeshEvent>()
             .add_stage(MeshStage::DelaunayMeshUpdate, SystemStage::parallel())
             .add_stage_after(
                 MeshStage::DelaunayMeshUpdate,
@@ -27,42 +31,67 @@ impl Plugin for MeshDraw {
             .add_system_to_stage(MeshStage::DelaunayMeshUpdate, swap_mesh_dedges)
             .add_system_to_stage(
                 MeshStage::DelaunayMeshRead,
-                update_mesh_positions.label("mesh positions"),
-            )
-            .add_system_to_stage(MeshStage::DelaunayMeshRead, handle_notify_mesh_events);
+                sync_mesh_changes.label("mesh positions"),
+            );
     }
 }
 
 /// Component form of PrimalDEdgeEntity. Probably a better way to do this.
 #[derive(Component, Clone, Copy, PartialEq)]
-pub struct PDEdgeEntity(pub usize);
+pub struct PDEdgeEntity(pub usize, pub Generation);
 impl From<PrimalDEdgeEntity> for PDEdgeEntity {
     fn from(e: PrimalDEdgeEntity) -> Self {
-        Self(e.0)
+        Self(e.0, e.1)
     }
 }
 impl From<PDEdgeEntity> for PrimalDEdgeEntity {
     fn from(e: PDEdgeEntity) -> Self {
-        Self(e.0)
+        Self(e.0, e.1)
     }
 }
 pub enum MeshEvent {
     Swap(PDEdgeEntity),
+    Undo,
+    Redo,
     // Insert(Vec2),
 }
 
-fn swap_mesh_dedges(mut mesh_events: EventReader<MeshEvent>, mesh: NonSend<DelaunayMesh>) {
+/// Swaps made while no animation is running, which can be undone.
+#[derive(Default)]
+pub struct MeshHistory(pub History<GeometricVertex, VoronoiVertex, ()>);
+
+fn swap_mesh_dedges(
+    mut mesh_events: EventReader<MeshEvent>,
+    mut mesh: ResMut<DelaunayMesh>,
+    mut history: ResMut<MeshHistory>,
+    animation_state: Res<State<AnimationState>>,
+) {
     for mesh_event in mesh_events.iter() {
         match mesh_event {
-            MeshEvent::Swap(e) => mesh.swap_primal((*e).into()),
-            _ => (),
+            MeshEvent::Swap(e) => {
+                mesh.begin();
+                mesh.swap_primal((*e).into());
+                let swap = mesh.commit();
+                // animations also insert edges, which aren't recorded
+                if *animation_state.current() == AnimationState::Stopped {
+                    history.0.push(swap);
+                } else {
+                    history.0.clear();
+                }
+            }
+            MeshEvent::Undo => {
+                history.0.undo(&mut mesh);
+            }
+            MeshEvent::Redo => {
+                history.0.redo(&mut mesh);
+            }
         }
     }
 }
 
 // fn insert_node(
 //     mut mesh_events: EventReader<MeshEvent>,
-//     mut mesh: NonSendMut<DelaunayMesh>,
+//     mut mesh: ResMut<DelaunayMesh>,
 //     mut selected_dedge: ResMut<SelectedDedge>,
 // ) {
 //     for mesh_event in mesh_events.iter() {
@@ -79,7 +108,7 @@ use super::default_arrows::{self, DefaultArrowsParam};
 
 fn insert_initial_mesh_into_world(
     mut commands: Commands,
-    mesh: NonSend<DelaunayMesh>,
+    mut mesh: ResMut<DelaunayMesh>,
     red_arrow_frame: Query<
         Entity,
         (
@@ -91,46 +120,42 @@ fn insert_initial_mesh_into_world(
     let red_arrow_frame = red_arrow_frame.single();
     // let white_arrow_frame = white_arrow_frame.single();
 
-    for (ent, dedge) in mesh
-        .primal_dedges
-        .iter()
-        .enumerate()
-        .map(|(i, d)| (PrimalDEdgeEntity(i), d))
-    {
-        if let Some(dedge) = dedge {
-            let origin = dedge.borrow().org;
-            let origin = mesh.get_vertex(origin).borrow().clone();
-
-            let dest = mesh.get_primal(ent.sym()).borrow().org;
-            let dest = mesh.get_vertex(dest).borrow().clone();
-
-            // let arrow_frame = if !mesh.is_delaunay(ent) {
-            //     red_arrow_frame
-            // } else {
-            //     white_arrow_frame
-            // };
-
-            commands
-                .spawn()
-                .insert(bevy_arrow::Arrow {
-                    tail: Vec3::new(origin.x as f32, origin.y as f32, 0.0),
-                    head: Vec3::new(dest.x as f32, dest.y as f32, 0.0),
-                    arrow_frame: red_arrow_frame,
-                    width: 16.0,
-                })
-                .insert(PDEdgeEntity::from(ent));
-        }
+    mesh.start_change_log();
+    for ent in mesh.primal_dedges() {
+        // let arrow_frame = if !mesh.is_delaunay(ent) {
+        //     red_arrow_frame
+        // } else {
+        //     white_arrow_frame
+        // };
+
+        spawn_arrow(&mut commands, &mesh, ent, red_arrow_frame);
     }
 }
 
-pub enum NotifyMeshEvent {
-    DEdgeInserted(PDEdgeEntity),
-    // EdgeRemoved(PDEdgeEntity),
+fn spawn_arrow(
+    commands: &mut Commands,
+    mesh: &DelaunayMesh,
+    ent: PrimalDEdgeEntity,
+    arrow_frame: Entity,
+) {
+    let cursor = mesh.primal(ent);
+    let origin = *cursor.org();
+    let dest = *cursor.dest();
+    commands
+        .spawn()
+        .insert(bevy_arrow::Arrow {
+            tail: Vec3::new(origin.x as f32, origin.y as f32, 0.0),
+            head: Vec3::new(dest.x as f32, dest.y as f32, 0.0),
+            arrow_frame,
+            width: 16.0,
+        })
+        .insert(PDEdgeEntity::from(ent));
 }
 
-fn handle_notify_mesh_events(
+/// Spawns, despawns and moves arrows as the mesh's change log reports edits.
+fn sync_mesh_changes(
     mut commands: Commands,
-    mesh: NonSend<DelaunayMesh>,
+    mut mesh: ResMut<DelaunayMesh>,
     red_arrow_frame: Query<
         Entity,
         (
@@ -138,46 +163,62 @@ fn handle_notify_mesh_events(
             Without<default_arrows::PulsingArrowFrame>,
         ),
     >,
-    mut notify_mesh_events: EventReader<NotifyMeshEvent>,
+    mut arrows: Query<(Entity, &mut bevy_arrow::Arrow, &PDEdgeEntity)>,
+    mut history: ResMut<MeshHistory>,
 ) {
-    for event in notify_mesh_events.iter() {
-        match *event {
-            NotifyMeshEvent::DEdgeInserted(pde) => {
-                let cursor = mesh.primal(pde.into());
-                let origin = cursor.org().borrow();
-                let dest = cursor.dest().borrow();
-                commands
-                    .spawn()
-                    .insert(bevy_arrow::Arrow {
-                        tail: Vec3::new(origin.x as f32, origin.y as f32, 0.0),
-                        head: Vec3::new(dest.x as f32, dest.y as f32, 0.0),
-                        arrow_frame: red_arrow_frame.single(),
-                        width: 16.0,
-                    })
-                    .insert(pde);
-            } // NotifyMeshEvent::EdgeRemoved(_) => todo!(),
+    let mut created = Vec::new();
+    let mut deleted = Vec::new();
+    let mut moved = HashSet::default();
+    let mut move_all = false;
+    let mut reset = false;
+    for change in mesh.drain_changes() {
+        match change {
+            MeshChange::EdgeCreated(e) => created.push(e),
+            MeshChange::EdgeDeleted(e) => deleted.push(e),
+            MeshChange::Swapped(e) | MeshChange::PrimalChanged(e) => {
+                moved.insert(e.edge());
+            }
+            MeshChange::VertexMoved(_) => move_all = true,
+            MeshChange::Reset => reset = true,
+            _ => (),
         }
     }
-}
+    if !created.is_empty() || !deleted.is_empty() {
+        // insertions aren't recorded, so earlier swaps can't be undone past them
+        history.0.clear();
+    }
 
+    let red_arrow_frame = red_arrow_frame.single();
+    if reset {
+        for (arrow, _, _) in arrows.iter() {
+            commands.entity(arrow).despawn();
+        }
+        for ent in mesh.primal_dedges() {
+            spawn_arrow(&mut commands, &mesh, ent, red_arrow_frame);
+        }
+        return;
+    }
 
-fn update_mesh_positions(
-    mesh: NonSend<DelaunayMesh>,
-    mut query: Query<(&mut bevy_arrow::Arrow, &PDEdgeEntity)>,
-) {
-    for (mut arrow, ent) in query.iter_mut() {
+    for (arrow_entity, mut arrow, ent) in arrows.iter_mut() {
         let ent = PrimalDEdgeEntity::from(*ent);
-        let dedge = mesh.get_primal(ent);
-
-        let origin = dedge.borrow().org;
-        let origin = mesh.get_vertex(origin).borrow().clone();
-
-        let dest = mesh.get_primal(ent.sym()).borrow().org;
-        let dest = mesh.get_vertex(dest).borrow().clone();
-
-        arrow.tail.x = origin.x as f32;
-        arrow.tail.y = origin.y as f32;
-        arrow.head.x = dest.x as f32;
-        arrow.head.y = dest.y as f32;
+        if deleted.iter().any(|e| e.edge() == ent.edge() && e.1 == ent.1) {
+            commands.entity(arrow_entity).despawn();
+        } else if move_all || moved.contains(&ent.edge()) {
+            let cursor = mesh.primal(ent);
+            let origin = *cursor.org();
+            let dest = *cursor.dest();
+
+            arrow.tail.x = origin.x as f32;
+            arrow.tail.y = origin.y as f32;
+            arrow.head.x = dest.x as f32;
+            arrow.head.y = dest.y as f32;
+        }
+    }
+    for ent in created {
+        // it may have been deleted again since
+        if mesh.try_get_primal(ent).is_ok() {
+            spawn_arrow(&mut commands, &mesh, ent, red_arrow_frame);
+            spawn_arrow(&mut commands, &mesh, ent.sym(), red_arrow_frame);
+        }
     }
 }
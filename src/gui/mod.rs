@@ -33,7 +33,7 @@ pub fn explore_mesh(mesh: DelaunayMesh) {
         .add_plugin(mouse::SimpleMouse)
         .add_plugin(bevy_arrow::ArrowPlugin)
         .add_plugin(default_arrows::DefaultArrows)
-        .insert_non_send_resource(mesh)
+        .insert_resource(mesh)
         .add_plugin(mesh_draw::MeshDraw)
         .add_plugin(animate_mesh::AnimateMesh)
         .insert_resource(ClearColor(Color::WHITE))
@@ -107,7 +107,8 @@ fn ui_system(
     edges: Query<&mesh_draw::PDEdgeEntity>,
     active_dedge: Res<animate_mesh::ActiveDedge>,
     mut spread: ResMut<f32>,
-    mut mesh: NonSendMut<DelaunayMesh>,
+    mut mesh: ResMut<DelaunayMesh>,
+    history: Res<mesh_draw::MeshHistory>,
     target_point: Query<&Transform, With<PointTarget>>,
     mut mesh_events: EventWriter<mesh_draw::MeshEvent>,
     mut animate_events: EventWriter<animate_mesh::AnimateMeshEvent<'static>>,
@@ -130,6 +131,20 @@ fn ui_system(
         {
             mesh_events.send(mesh_draw::MeshEvent::Swap(active_dedge.0.unwrap()));
         };
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(history.0.can_undo(), egui::widgets::Button::new("Undo"))
+                .clicked()
+            {
+                mesh_events.send(mesh_draw::MeshEvent::Undo);
+            }
+            if ui
+                .add_enabled(history.0.can_redo(), egui::widgets::Button::new("Redo"))
+                .clicked()
+            {
+                mesh_events.send(mesh_draw::MeshEvent::Redo);
+            }
+        });
         if ui.button("locate").clicked() {
             let x = target_point.single().translation;
             let found = mesh.locate_point(Point2::new(x.x, x.y));
@@ -177,7 +192,7 @@ fn ui_system(
 //     mut egui_context: ResMut<EguiContext>,
 //     mut ui_window_rect: ResMut<UiWindowRect>,
 //     edges: Query<&mesh_draw::PDEdgeEntity>,
-//     mut mesh: NonSendMut<DelaunayMesh>,
+//     mut mesh: ResMut<DelaunayMesh>,
 //     target_point: Query<&Transform, With<PointTarget>>,
 //     mut mesh_events: EventWriter<mesh_draw::MeshEvent>,
 //     mut animate_events: EventWriter<animate_mesh::AnimateMeshEvent<'static>>,
@@ -1,13 +1,29 @@
 pub mod delaunay_voronoi;
+pub mod formats;
 pub mod geometry;
 pub mod mesh;
+pub mod predicates;
+pub mod svg;
 pub mod topological;
+pub mod voronoi;
 
 #[cfg(test)]
 mod tests {
+    use cgmath::Point3;
+
     use crate::{
-        delaunay_voronoi::{DelaunayMesh, VoronoiVertex},
+        delaunay_voronoi::{DelaunayMesh, InsertionStep, VoronoiVertex},
+        formats::{PolygonSoup, ReadError},
+        geometry,
+        mesh::{
+            quad::{FaceEntity, PrimalDEdgeEntity, VertexEntity},
+            History, Mesh, MeshChange, MeshCursor, MeshError, MeshExtension, PolygonError,
+            Violation,
+        },
+        predicates,
+        svg::SvgOptions,
         topological::TopologicalMesh,
+        voronoi::clip_rectangle,
     };
 
     #[test]
@@ -44,7 +60,7 @@ mod tests {
         let d1 = e1.rot();
         let d2 = e2.rot();
 
-        assert_eq!(mesh.dual(d1).get().borrow().org, dummy);
+        assert_eq!(mesh.dual(d1).get().org, dummy);
 
         // check dual topology
         assert_eq!(mesh.dual(d1).lnext().id(), d1);
@@ -73,8 +89,8 @@ mod tests {
         let d2 = e2.rot();
         let d3 = e3.rot();
 
-        assert_eq!(mesh.primal(e3).sym().get().borrow().org, a);
-        assert_eq!(mesh.primal(e2.sym()).dest().borrow().to_string(), "B");
+        assert_eq!(mesh.primal(e3).sym().get().org, a);
+        assert_eq!(mesh.primal(e2.sym()).dest().to_string(), "B");
         assert_eq!(mesh.primal(e1).lnext().id(), e2);
         assert_eq!(mesh.primal(e1).sym().lnext().id(), e3.sym());
         assert_eq!(mesh.primal(e3).lnext().id(), e1);
@@ -82,6 +98,7 @@ mod tests {
         assert_eq!(mesh.primal(e2).lnext().id(), e3);
         assert_eq!(mesh.primal(e2).sym().lnext().id(), e1.sym());
         assert_eq!(mesh.primal(e1.sym()).onext().id(), e2);
+        assert_eq!(mesh.validate(), Ok(()));
     }
 
     #[test]
@@ -122,4 +139,1352 @@ mod tests {
         let e1 = mesh.make_edge(a, b, dummy, dummy);
         let e2 = mesh.make_edge(c, d, dummy, dummy);
     }
+
+    #[test]
+    fn deleted_slots_are_reused() {
+        let mut mesh = TopologicalMesh::new();
+        let a = mesh.insert_vertex("A");
+        let b = mesh.insert_vertex("B");
+        let dummy = mesh.insert_face("(face)");
+
+        let e1 = mesh.make_edge(a, b, dummy, dummy);
+        mesh.delete_primal(e1);
+        let e2 = mesh.make_edge(b, a, dummy, dummy);
+        assert_eq!(e2.edge(), e1.edge());
+        assert_ne!(e2, e1);
+        assert_eq!(mesh.quad_edges.slot_count(), 1);
+
+        mesh.delete_verted(a);
+        let c = mesh.insert_vertex("C");
+        assert_eq!(c.0, a.0);
+        assert_ne!(c, a);
+        assert_eq!(*mesh.get_vertex(c), "C");
+    }
+
+    #[test]
+    #[should_panic(expected = "stale")]
+    fn stale_dedge_is_detected() {
+        let mut mesh = TopologicalMesh::new();
+        let a = mesh.insert_vertex("A");
+        let b = mesh.insert_vertex("B");
+        let dummy = mesh.insert_face("(face)");
+
+        let e1 = mesh.make_edge(a, b, dummy, dummy);
+        mesh.delete_primal(e1);
+        mesh.make_edge(a, b, dummy, dummy);
+        mesh.get_primal(e1);
+    }
+
+    #[test]
+    fn fallible_api_reports_bad_handles() {
+        let mut mesh = TopologicalMesh::new();
+        let a = mesh.insert_vertex("A");
+        let b = mesh.insert_vertex("B");
+        let dummy = mesh.insert_face("(face)");
+
+        let e1 = mesh.make_edge(a, b, dummy, dummy);
+        let e2 = mesh.connect_vertex(e1, a);
+        mesh.delete_primal(e2);
+
+        assert_eq!(
+            mesh.try_get_primal(e2).unwrap_err(),
+            MeshError::DeletedEntity(e2.into())
+        );
+        assert_eq!(
+            mesh.try_splice_primal(e1, e2),
+            Err(MeshError::DeletedEntity(e2.into()))
+        );
+        assert_eq!(
+            mesh.try_connect_primal(e1, PrimalDEdgeEntity(8, 0)),
+            Err(MeshError::DanglingEntity(PrimalDEdgeEntity(8, 0).into()))
+        );
+        assert_eq!(mesh.quad_edges.live_count(), 1);
+    }
+
+    #[test]
+    fn mesh_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<DelaunayMesh>();
+        assert_send_sync::<TopologicalMesh<&str>>();
+    }
+
+    #[test]
+    fn cursor_algebra_identities() {
+        let mut mesh = TopologicalMesh::new();
+        let a = mesh.insert_vertex("A");
+        let b = mesh.insert_vertex("B");
+        let c = mesh.insert_vertex("C");
+        let infinity = mesh.insert_face("(infinity)");
+        let inside = mesh.insert_face("(inside)");
+
+        let e1 = mesh.make_edge(a, b, inside, infinity);
+        let e2 = mesh.connect_vertex(e1, c);
+        mesh.connect_primal(e2, e1);
+
+        for e in mesh.primal_dedges() {
+            let e = mesh.primal(e);
+            assert_eq!(e.rot().rot().id(), e.sym().id());
+            assert_eq!(e.rot().rot_inv().id(), e.id());
+            assert_eq!(e.oprev().onext().id(), e.id());
+            assert_eq!(e.lnext().lprev().id(), e.id());
+            assert_eq!(e.rnext().rprev().id(), e.id());
+            assert_eq!(e.dnext().dprev().id(), e.id());
+            assert_eq!(e.lnext().lnext().lnext().id(), e.id());
+            assert_eq!(e.rot().lnext().id(), e.onext().rot().id());
+            assert_eq!(e.rot().left(), e.org());
+            assert_eq!(e.rot().right(), e.dest());
+
+            let mut walk = e;
+            walk.lnext_mut().lnext_mut().lnext_mut();
+            assert_eq!(walk.id(), e.id());
+        }
+
+        let e1 = mesh.primal(e1);
+        assert_eq!(*e1.left(), "(inside)");
+        assert_eq!(*e1.right(), "(infinity)");
+        assert_eq!(*e1.rot().org(), "(infinity)");
+    }
+
+    #[test]
+    fn splice_dual_is_an_involution() {
+        let mut mesh = TopologicalMesh::new();
+        let a = mesh.insert_vertex("A");
+        let b = mesh.insert_vertex("B");
+        let c = mesh.insert_vertex("C");
+        let infinity = mesh.insert_face("(infinity)");
+        let inside = mesh.insert_face("(inside)");
+
+        let e1 = mesh.make_edge(a, b, inside, infinity);
+        let e2 = mesh.connect_vertex(e1, c);
+        let e3 = mesh.connect_primal(e2, e1);
+
+        let d1 = e1.rot();
+        let d3 = e3.rot();
+        let d1_onext = mesh.dual(d1).onext().id();
+        let d3_onext = mesh.dual(d3).onext().id();
+        mesh.splice_dual(d1, d3);
+        assert_eq!(mesh.dual(d1).onext().id(), d3_onext);
+        assert_eq!(mesh.dual(d3).onext().id(), d1_onext);
+        assert!(mesh.validate().is_err());
+
+        mesh.splice_dual(d1, d3);
+        assert_eq!(mesh.validate(), Ok(()));
+    }
+
+    #[test]
+    fn iterate_triangle() {
+        let mut mesh = TopologicalMesh::new();
+        let a = mesh.insert_vertex("A");
+        let b = mesh.insert_vertex("B");
+        let c = mesh.insert_vertex("C");
+        let lonely = mesh.insert_vertex("D");
+        let infinity = mesh.insert_face("(infinity)");
+        let inside = mesh.insert_face("(inside)");
+
+        let e1 = mesh.make_edge(a, b, inside, infinity);
+        let e2 = mesh.connect_vertex(e1, c);
+        let e3 = mesh.connect_primal(e2, e1);
+
+        assert_eq!(mesh.edges().count(), 3);
+
+        let stars = mesh.vertex_stars().collect::<Vec<_>>();
+        assert_eq!(stars.len(), 4);
+        for (v, star) in &stars {
+            let expected = if *v == lonely { 0 } else { 2 };
+            assert_eq!(star.len(), expected);
+            assert!(star.iter().all(|e| mesh.get_primal(*e).org == *v));
+        }
+
+        let boundaries = mesh.face_boundaries().collect::<Vec<_>>();
+        assert_eq!(
+            boundaries,
+            vec![
+                (infinity, vec![e1.sym(), e3.sym(), e2.sym()]),
+                (inside, vec![e1, e2, e3]),
+            ]
+        );
+
+        let mut neighbors = mesh.get_vertex_neighbors(e1).collect::<Vec<_>>();
+        neighbors.sort_by_key(|v| v.0);
+        assert_eq!(neighbors, vec![b, c]);
+        assert!(mesh.get_face_neighbors(e1).all(|face| face == infinity));
+    }
+
+    #[test]
+    fn compact_fills_holes() {
+        let mut mesh = TopologicalMesh::new();
+        let doomed = mesh.insert_vertex("doomed");
+        let a = mesh.insert_vertex("A");
+        let b = mesh.insert_vertex("B");
+        let c = mesh.insert_vertex("C");
+        let doomed_face = mesh.insert_face("(doomed)");
+        let infinity = mesh.insert_face("(infinity)");
+        let inside = mesh.insert_face("(inside)");
+
+        let doomed_edge = mesh.make_edge(a, b, infinity, infinity);
+        let e1 = mesh.make_edge(a, b, inside, infinity);
+        let e2 = mesh.connect_vertex(e1, c);
+        let e3 = mesh.connect_primal(e2, e1);
+        mesh.delete_primal(doomed_edge);
+        mesh.delete_verted(doomed);
+        mesh.delete_face(doomed_face);
+
+        let remap = mesh.compact();
+        assert_eq!(mesh.quad_edges.slot_count(), 3);
+        assert_eq!(mesh.vertices.slot_count(), 3);
+        assert_eq!(mesh.faces.slot_count(), 2);
+        assert_eq!(mesh.validate(), Ok(()));
+
+        assert_eq!(remap.primal(doomed_edge), None);
+        assert_eq!(remap.vertex(doomed), None);
+        assert_eq!(remap.face(doomed_face), None);
+        let e1 = remap.primal(e1).unwrap();
+        let e3 = remap.primal(e3).unwrap();
+        assert_eq!(mesh.primal(e1).lprev().id(), e3);
+        assert_eq!(*mesh.primal(e1).org(), "A");
+        assert_eq!(*mesh.get_face(remap.face(inside).unwrap()), "(inside)");
+        assert_eq!(mesh.get_vertex(remap.vertex(c).unwrap()), &"C");
+    }
+
+    #[test]
+    fn compact_rejects_untranslated_handles() {
+        let mut mesh = TopologicalMesh::new();
+        let doomed = mesh.insert_vertex("doomed");
+        let a = mesh.insert_vertex("A");
+        let b = mesh.insert_vertex("B");
+        let infinity = mesh.insert_face("(infinity)");
+        let e = mesh.make_edge(a, b, infinity, infinity);
+        mesh.delete_verted(doomed);
+
+        let remap = mesh.compact();
+        assert_eq!(mesh.get_vertex(remap.vertex(a).unwrap()), &"A");
+        // "B" moved into the slot of `a`, at a generation no old handle has
+        assert_eq!(mesh.try_get_vertex(a).unwrap_err(), MeshError::DeletedEntity(a.into()));
+        // handles whose slot didn't move must be translated too
+        assert_eq!(mesh.try_get_primal(e).unwrap_err(), MeshError::DeletedEntity(e.into()));
+        assert_eq!(
+            mesh.try_get_face(infinity).unwrap_err(),
+            MeshError::DeletedEntity(infinity.into())
+        );
+        // as must handles to slots appended after compacting
+        let c = mesh.insert_vertex("C");
+        assert_eq!(c.0, b.0);
+        assert_eq!(mesh.try_get_vertex(b).unwrap_err(), MeshError::DeletedEntity(b.into()));
+        assert_eq!(mesh.validate(), Ok(()));
+    }
+
+    #[test]
+    fn polygons_with_boundary() {
+        // two triangles sharing the diagonal of a square
+        let mesh = TopologicalMesh::from_polygons(
+            ["A", "B", "C", "D"],
+            &[[0, 1, 2], [0, 2, 3]],
+            |polygon| ["(abc)", "(acd)"].get(polygon.unwrap_or(2)).unwrap_or(&"(outside)"),
+        )
+        .unwrap();
+        assert_eq!(mesh.validate(), Ok(()));
+        assert_eq!(mesh.edges().count(), 5);
+        assert_eq!(mesh.faces.live_count(), 3);
+
+        let boundaries = mesh.face_boundaries().collect::<Vec<_>>();
+        assert_eq!(boundaries[0].1.len(), 3);
+        assert_eq!(boundaries[1].1.len(), 3);
+        assert_eq!(*mesh.get_face(boundaries[2].0), "(outside)");
+        assert_eq!(boundaries[2].1.len(), 4);
+    }
+
+    #[test]
+    fn closed_polygons() {
+        let cube = [
+            [0, 3, 2, 1],
+            [4, 5, 6, 7],
+            [0, 1, 5, 4],
+            [1, 2, 6, 5],
+            [2, 3, 7, 6],
+            [3, 0, 4, 7],
+        ];
+        let mesh = TopologicalMesh::from_polygons(0..8, &cube, |_| 0).unwrap();
+        assert_eq!(mesh.validate(), Ok(()));
+        assert_eq!(mesh.edges().count(), 12);
+        assert_eq!(mesh.faces.live_count(), 6);
+        assert!(mesh.vertex_stars().all(|(_, star)| star.len() == 3));
+    }
+
+    #[test]
+    fn reject_non_manifold_polygons() {
+        let fin = TopologicalMesh::from_polygons(0..5, &[[0, 1, 2], [1, 0, 3], [0, 1, 4]], |_| 0);
+        assert_eq!(fin.unwrap_err(), PolygonError::NonManifoldEdge { org: 0, dest: 1 });
+
+        let flipped = TopologicalMesh::from_polygons(0..4, &[[0, 1, 2], [0, 1, 3]], |_| 0);
+        assert_eq!(flipped.unwrap_err(), PolygonError::NonManifoldEdge { org: 0, dest: 1 });
+
+        let bowtie = TopologicalMesh::from_polygons(0..5, &[[0, 1, 2], [0, 3, 4]], |_| 0);
+        assert_eq!(bowtie.unwrap_err(), PolygonError::NonManifoldVertex(0));
+
+        // two closed tetrahedra touching at a vertex
+        let pinched = [
+            [0, 2, 1],
+            [0, 1, 3],
+            [0, 3, 2],
+            [1, 2, 3],
+            [0, 5, 4],
+            [0, 4, 6],
+            [0, 6, 5],
+            [4, 5, 6],
+        ];
+        let pinched = TopologicalMesh::from_polygons(0..7, &pinched, |_| 0);
+        assert_eq!(pinched.unwrap_err(), PolygonError::NonManifoldVertex(0));
+
+        let missing = TopologicalMesh::from_polygons(0..3, &[[0, 1, 3]], |_| 0);
+        assert_eq!(
+            missing.unwrap_err(),
+            PolygonError::VertexOutOfRange {
+                polygon: 0,
+                vertex: 3
+            }
+        );
+
+        let degenerate = TopologicalMesh::from_polygons(0..3, &[vec![0, 1, 2], vec![0, 1]], |_| 0);
+        assert_eq!(degenerate.unwrap_err(), PolygonError::DegeneratePolygon(1));
+    }
+
+    #[test]
+    fn euler_operators() {
+        // a square fanned around its center vertex 4
+        let fan = [[0, 1, 4], [1, 2, 4], [2, 3, 4], [3, 0, 4]];
+        let square = || TopologicalMesh::from_polygons(0..5, &fan, |_| 0).unwrap();
+
+        let mut mesh = square();
+        let spoke = mesh.primal_dedges().find(|&e| *mesh.primal(e).org() == 4);
+        let quad = mesh.remove_vertex(spoke.unwrap());
+        assert_eq!(mesh.validate(), Ok(()));
+        assert_eq!(mesh.vertices.live_count(), 4);
+        assert_eq!(mesh.faces.live_count(), 2);
+        let sides = mesh.face_boundaries().find(|(f, _)| *f == quad).unwrap().1;
+        assert_eq!(sides.len(), 4);
+
+        let (diagonal, half) = mesh.split_face(sides[0], sides[3], 1);
+        assert_eq!(mesh.validate(), Ok(()));
+        assert_eq!(*mesh.primal(diagonal).left(), 0);
+        assert_eq!(*mesh.primal(diagonal).right(), 1);
+        assert_eq!(mesh.get_lnext_ring(diagonal).count(), 3);
+        assert_eq!(mesh.get_lnext_ring(diagonal.sym()).count(), 3);
+
+        assert_eq!(mesh.merge_faces(diagonal), quad);
+        assert_eq!(mesh.validate(), Ok(()));
+        assert!(mesh.try_get_face(half).is_err());
+
+        let middle = mesh.insert_vertex(5);
+        let rest = mesh.split_edge(sides[0], middle);
+        assert_eq!(mesh.validate(), Ok(()));
+        assert_eq!(*mesh.primal(sides[0]).dest(), 5);
+        assert_eq!(mesh.get_lnext_ring(rest).count(), 5);
+
+        mesh.collapse_edge(sides[0]);
+        assert_eq!(mesh.validate(), Ok(()));
+        assert_eq!(mesh.edges().count(), 4);
+        assert!(mesh.try_get_vertex(middle).is_err());
+
+        // collapsing a spoke flattens the two triangles either side of it
+        let mut mesh = square();
+        let spoke = mesh.primal_dedges().find(|&e| *mesh.primal(e).dest() == 4);
+        mesh.collapse_edge(spoke.unwrap());
+        assert_eq!(mesh.validate(), Ok(()));
+        assert_eq!(mesh.edges().count(), 5);
+        assert_eq!(mesh.faces.live_count(), 3);
+        assert!(mesh.face_boundaries().all(|(_, sides)| matches!(sides.len(), 3 | 4)));
+    }
+
+    #[test]
+    fn remove_cut_vertex() {
+        // two triangles of a fanned square, joined only at the center vertex 4
+        let fan = [[0, 1, 4], [1, 2, 4], [2, 3, 4], [3, 0, 4]];
+        let dedge = |mesh: &TopologicalMesh<i32>, org, dest| {
+            mesh.primal_dedges()
+                .find(|&e| *mesh.primal(e).org() == org && *mesh.primal(e).dest() == dest)
+                .unwrap()
+        };
+        let bowtie = || {
+            let mut mesh = TopologicalMesh::from_polygons(0..5, &fan, |_| 0).unwrap();
+            mesh.merge_faces(dedge(&mesh, 1, 0));
+            mesh.merge_faces(dedge(&mesh, 3, 2));
+            mesh
+        };
+
+        let mut mesh = bowtie();
+        mesh.remove_vertex(dedge(&mesh, 4, 0));
+        assert_eq!(mesh.validate(), Ok(()));
+        assert_eq!(mesh.vertices.live_count(), 4);
+        assert_eq!(mesh.edges().count(), 2);
+        assert_eq!(mesh.faces.live_count(), 2);
+
+        // with both triangles opened up, the outer face is all there is to share out, and a
+        // pendant vertex goes with the vertex it hangs from
+        let mut mesh = bowtie();
+        mesh.merge_faces(dedge(&mesh, 4, 1));
+        mesh.merge_faces(dedge(&mesh, 4, 3));
+        let pendant = mesh.insert_vertex(5);
+        mesh.connect_vertex(dedge(&mesh, 0, 4), pendant);
+        assert_eq!(mesh.validate(), Ok(()));
+        assert_eq!(mesh.faces.live_count(), 1);
+        mesh.remove_vertex(dedge(&mesh, 4, 0));
+        assert_eq!(mesh.validate(), Ok(()));
+        assert_eq!(mesh.vertices.live_count(), 4);
+        assert_eq!(mesh.edges().count(), 2);
+        assert_eq!(mesh.faces.live_count(), 2);
+    }
+
+    #[test]
+    fn undo_and_redo_transactions() {
+        let mut mesh =
+            TopologicalMesh::from_polygons(0..4, &[[0, 1, 2], [0, 2, 3]], |_| 0).unwrap();
+        let diagonal = mesh
+            .primal_dedges()
+            .find(|&e| *mesh.primal(e).org() == 0 && *mesh.primal(e).dest() == 2)
+            .unwrap();
+        let mut history = History::default();
+        let mut states = vec![format!("{:?}", mesh)];
+
+        mesh.begin();
+        mesh.swap_primal(diagonal);
+        history.push(mesh.commit());
+        states.push(format!("{:?}", mesh));
+
+        mesh.begin();
+        mesh.merge_faces(diagonal);
+        mesh.insert_vertex(4);
+        history.push(mesh.commit());
+        states.push(format!("{:?}", mesh));
+
+        assert!(history.undo(&mut mesh));
+        assert_eq!(format!("{:?}", mesh), states[1]);
+        assert!(history.undo(&mut mesh));
+        assert_eq!(format!("{:?}", mesh), states[0]);
+        assert!(!history.undo(&mut mesh));
+
+        assert!(history.redo(&mut mesh));
+        assert_eq!(format!("{:?}", mesh), states[1]);
+        assert!(history.redo(&mut mesh));
+        assert_eq!(format!("{:?}", mesh), states[2]);
+        assert!(!history.can_redo());
+        assert_eq!(mesh.validate(), Ok(()));
+
+        // a rolled back edit leaves no trace, free lists included
+        let spoke = mesh.primal_dedges().find(|&e| *mesh.primal(e).org() == 1);
+        mesh.begin();
+        mesh.remove_vertex(spoke.unwrap());
+        mesh.rollback();
+        assert_eq!(format!("{:?}", mesh), states[2]);
+
+        // and so do payload writes
+        let (index, generation, _) = mesh.faces.iter().next().unwrap();
+        let face = FaceEntity(index, generation);
+        mesh.begin();
+        *mesh.get_vertex_mut(VertexEntity(0, 0)) = 10;
+        *mesh.get_face_mut(face) = 10;
+        mesh.rollback();
+        assert_eq!(format!("{:?}", mesh), states[2]);
+    }
+
+    #[test]
+    fn change_log_reports_edits() {
+        let mut mesh =
+            TopologicalMesh::from_polygons(0..4, &[[0, 1, 2], [0, 2, 3]], |_| 0).unwrap();
+        let diagonal = mesh
+            .primal_dedges()
+            .find(|&e| *mesh.primal(e).org() == 0 && *mesh.primal(e).dest() == 2)
+            .unwrap();
+        assert_eq!(mesh.drain_changes().count(), 0);
+
+        mesh.start_change_log();
+        mesh.swap_primal(diagonal);
+        let changes = mesh.drain_changes().collect::<Vec<_>>();
+        assert_eq!(changes.len(), 5);
+        assert!(changes[..4]
+            .iter()
+            .all(|change| matches!(change, MeshChange::Spliced(..))));
+        assert_eq!(changes[4], MeshChange::Swapped(diagonal));
+
+        mesh.begin();
+        let v = mesh.insert_vertex(4);
+        let e = mesh.connect_vertex(diagonal, v);
+        let transaction = mesh.commit();
+        assert_eq!(
+            mesh.drain_changes().collect::<Vec<_>>(),
+            [
+                MeshChange::VertexInserted(v),
+                MeshChange::EdgeCreated(e),
+                MeshChange::Spliced(e, diagonal.sym()),
+            ]
+        );
+        mesh.revert(transaction);
+        assert_eq!(mesh.drain_changes().collect::<Vec<_>>(), [MeshChange::Reset]);
+
+        // only a label that changes is reported, and only once it has
+        let right = mesh.get_dual(diagonal.rot()).org;
+        let left = mesh.get_dual(diagonal.rot_inv()).org;
+        mesh.set_dual_org(diagonal.rot(), right);
+        assert_eq!(mesh.drain_changes().count(), 0);
+        mesh.set_dual_org(diagonal.rot(), left);
+        assert_eq!(
+            mesh.drain_changes().collect::<Vec<_>>(),
+            [MeshChange::FaceRelabelled(diagonal.rot())]
+        );
+        mesh.get_dual_mut(diagonal.rot()).org = right;
+        assert_eq!(
+            mesh.drain_changes().collect::<Vec<_>>(),
+            [MeshChange::DualChanged(diagonal.rot())]
+        );
+
+        mesh.stop_change_log();
+        mesh.delete_primal(diagonal);
+        assert_eq!(mesh.drain_changes().count(), 0);
+    }
+
+    #[test]
+    fn edge_payloads_follow_their_edges() {
+        let mut mesh: Mesh<i32, i32, &str, ()> =
+            Mesh::from_polygons(0..4, &[[0, 1, 2], [0, 2, 3]], |_| 0).unwrap();
+        let diagonal = mesh
+            .primal_dedges()
+            .find(|&e| *mesh.primal(e).org() == 0 && *mesh.primal(e).dest() == 2)
+            .unwrap();
+        assert_eq!(*mesh.get_edge(diagonal), "");
+        *mesh.get_edge_mut(diagonal.sym()) = "diagonal";
+        assert_eq!(*mesh.get_edge(diagonal), "diagonal");
+
+        mesh.swap_primal(diagonal);
+        assert_eq!(*mesh.get_edge(diagonal), "diagonal");
+
+        let middle = mesh.insert_vertex(4);
+        let rest = mesh.split_edge(diagonal, middle);
+        assert_eq!(*mesh.get_edge(rest), "diagonal");
+        assert_eq!(mesh.validate(), Ok(()));
+
+        let ring = mesh.get_lnext_ring(rest).collect::<Vec<_>>();
+        assert_eq!(ring.len(), 4);
+        let e = mesh.connect_primal_with(rest, ring[2], "spoke");
+        assert_eq!(*mesh.get_edge(e.sym()), "spoke");
+
+        mesh.delete_primal(e);
+        assert!(mesh.try_get_edge(e).is_err());
+        assert_eq!(mesh.edge_data.live_count(), mesh.edges().count());
+    }
+
+    #[test]
+    fn extension_follows_edge_edits() {
+        #[derive(Default)]
+        struct Counts {
+            edges: usize,
+            splices: usize,
+        }
+        impl<V, F, E> MeshExtension<V, F, E> for Counts {
+            fn edge_created(&mut self, _: PrimalDEdgeEntity) {
+                self.edges += 1;
+            }
+            fn edge_deleted(&mut self, _: PrimalDEdgeEntity) {
+                self.edges -= 1;
+            }
+            fn spliced(&mut self, _: PrimalDEdgeEntity, _: PrimalDEdgeEntity) {
+                self.splices += 1;
+            }
+        }
+
+        let mut mesh: Mesh<i32, i32, (), Counts> =
+            Mesh::from_polygons(0..4, &[[0, 1, 2], [0, 2, 3]], |_| 0).unwrap();
+        assert_eq!(mesh.cache.edges, 5);
+
+        mesh.begin();
+        let diagonal = mesh.edges().next().unwrap();
+        mesh.cache.splices = 0;
+        mesh.delete_primal(diagonal);
+        assert_eq!((mesh.cache.edges, mesh.cache.splices), (4, 2));
+
+        mesh.rollback();
+        assert_eq!((mesh.cache.edges, mesh.cache.splices), (0, 0));
+    }
+
+    #[test]
+    fn obj_round_trip() {
+        let obj = "\
+# a square split along its diagonal
+o square
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vn 0 0 1
+f 1//1 2//1 3//1
+f 1/1/1 -2/2/1 -1/3/1
+";
+        let soup = PolygonSoup::read_obj(obj.as_bytes()).unwrap();
+        assert_eq!(soup.polygons, vec![vec![0, 1, 2], vec![0, 2, 3]]);
+
+        let mesh: Mesh<Point3<f64>, VoronoiVertex, (), ()> = soup.to_mesh().unwrap();
+        assert_eq!(mesh.validate(), Ok(()));
+        assert_eq!(mesh.faces.live_count(), 3);
+
+        // the infinite outer face is left out
+        let written = PolygonSoup::from_mesh(&mesh);
+        assert_eq!(written.polygons.len(), 2);
+        let mut obj = Vec::new();
+        written.write_obj(&mut obj).unwrap();
+        assert_eq!(PolygonSoup::read_obj(obj.as_slice()).unwrap(), written);
+    }
+
+    #[test]
+    fn off_round_trip() {
+        let off = "\
+OFF
+# cube
+8 6 12
+0 0 0
+1 0 0
+1 1 0
+0 1 0
+0 0 1
+1 0 1
+1 1 1
+0 1 1
+4 0 3 2 1
+4 4 5 6 7
+4 0 1 5 4
+4 1 2 6 5
+4 2 3 7 6 255 0 0
+4 3 0 4 7
+";
+        let soup = PolygonSoup::read_off(off.as_bytes()).unwrap();
+        assert_eq!(soup.positions.len(), 8);
+        assert_eq!(soup.polygons[4], vec![2, 3, 7, 6]);
+
+        let mesh = soup.to_topological_mesh().unwrap();
+        assert_eq!(mesh.validate(), Ok(()));
+        assert_eq!(mesh.edges().count(), 12);
+
+        let mut written = Vec::new();
+        soup.write_off(&mut written).unwrap();
+        assert_eq!(PolygonSoup::read_off(written.as_slice()).unwrap(), soup);
+    }
+
+    #[test]
+    fn export_delaunay_triangles() {
+        use rand::Rng;
+
+        let mut mesh = DelaunayMesh::new();
+        let a = mesh.insert_vertex((-100.0, -100.0));
+        let b = mesh.insert_vertex((-100.0, 100.0));
+        let c = mesh.insert_vertex((100.0, 0.0));
+        let infinity = mesh.insert_face(VoronoiVertex::Infinite);
+        let inside = mesh.insert_face(VoronoiVertex::Finite(0.0, 0.0));
+        let e1 = mesh.make_edge(a, b, infinity, inside);
+        let e2 = mesh.connect_vertex(e1, c);
+        mesh.connect_primal(e2, e1);
+
+        let mut rng: rand::rngs::StdRng = rand::SeedableRng::seed_from_u64(2);
+        for _ in 0..20 {
+            mesh.insert_delaunay_vertex(
+                (rng.gen_range(-200.0..500.0), rng.gen_range(-300.0..300.0)).into(),
+            );
+        }
+
+        let soup = PolygonSoup::from_mesh(&mesh);
+        assert_eq!(soup.positions.len(), 23);
+        assert_eq!(soup.polygons.len(), mesh.faces.live_count() - 1);
+        assert!(soup.polygons.iter().all(|polygon| polygon.len() == 3));
+
+        let reloaded: DelaunayMesh = soup.to_mesh().unwrap();
+        assert_eq!(reloaded.validate(), Ok(()));
+        assert_eq!(reloaded.edges().count(), mesh.edges().count());
+    }
+
+    #[test]
+    fn svg_of_a_triangle() {
+        let mut mesh = DelaunayMesh::new();
+        let a = mesh.insert_vertex((0.0, 0.0));
+        let b = mesh.insert_vertex((0.0, 100.0));
+        let c = mesh.insert_vertex((100.0, 0.0));
+        let infinity = mesh.insert_face(VoronoiVertex::Infinite);
+        let inside = mesh.insert_face(VoronoiVertex::Finite(0.0, 0.0));
+        let e1 = mesh.make_edge(a, b, infinity, inside);
+        let e2 = mesh.connect_vertex(e1, c);
+        mesh.connect_primal(e2, e1);
+
+        let options = SvgOptions {
+            circumcircles: true,
+            edge_labels: true,
+            vertex_labels: true,
+            face_labels: true,
+            ..Default::default()
+        };
+        let svg = mesh.to_svg(&options);
+        assert_eq!(svg, mesh.to_svg(&options));
+        assert!(svg.starts_with("<svg ") && svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<line ").count(), 3 + 3);
+        assert_eq!(svg.matches("<circle ").count(), 1 + 3);
+        assert_eq!(svg.matches("<text ").count(), 6 + 3 + 1);
+        // the circumcenter of a right triangle is the midpoint of its hypotenuse
+        assert!(svg.contains(r#"<circle cx="57.071" cy="57.071" r="70.711"/>"#));
+    }
+
+    #[test]
+    fn reject_malformed_files() {
+        let obj = "v 0 0\nv 1 0\nv 0 1\nf 1 0 2\n";
+        assert!(matches!(
+            PolygonSoup::read_obj(obj.as_bytes()),
+            Err(ReadError::Syntax { line: 4, .. })
+        ));
+
+        let off = "OFF\n3 1 0\n0 0 0\n1 0 0\n";
+        assert!(matches!(
+            PolygonSoup::read_off(off.as_bytes()),
+            Err(ReadError::UnexpectedEnd(_))
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        use rand::Rng;
+
+        let mut mesh = DelaunayMesh::new();
+        let a = mesh.insert_vertex((-100.0, -100.0));
+        let b = mesh.insert_vertex((-100.0, 100.0));
+        let c = mesh.insert_vertex((100.0, 0.0));
+        let infinity = mesh.insert_face(VoronoiVertex::Infinite);
+        let inside = mesh.insert_face(VoronoiVertex::Finite(0.0, 0.0));
+        let e1 = mesh.make_edge(a, b, infinity, inside);
+        let e2 = mesh.connect_vertex(e1, c);
+        mesh.connect_primal(e2, e1);
+
+        let mut rng: rand::rngs::StdRng = rand::SeedableRng::seed_from_u64(1);
+        for _ in 0..20 {
+            mesh.insert_delaunay_vertex(
+                (rng.gen_range(-200.0..500.0), rng.gen_range(-300.0..300.0)).into(),
+            );
+        }
+        let doomed = mesh.make_edge(a, b, infinity, infinity);
+        mesh.delete_primal(doomed);
+
+        let json = serde_json::to_string(&mesh).unwrap();
+        let mut loaded: DelaunayMesh = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.validate(), Ok(()));
+        assert_eq!(
+            loaded.primal_dedges().collect::<Vec<_>>(),
+            mesh.primal_dedges().collect::<Vec<_>>()
+        );
+        for e in mesh.primal_dedges() {
+            assert_eq!(loaded.primal(e).onext().id(), mesh.primal(e).onext().id());
+            assert_eq!(loaded.primal(e).org(), mesh.primal(e).org());
+            assert_eq!(loaded.primal(e).left(), mesh.primal(e).left());
+        }
+
+        // the freed slot survives, with its generation
+        assert_eq!(
+            loaded.try_get_primal(doomed).unwrap_err(),
+            MeshError::DeletedEntity(doomed.into())
+        );
+        assert_eq!(loaded.make_edge(a, b, infinity, infinity).edge(), doomed.edge());
+    }
+
+    #[test]
+    fn stepped_insertion_matches_insert_delaunay_vertex() {
+        use rand::Rng;
+
+        let triangle = || {
+            let mut mesh = DelaunayMesh::new();
+            let a = mesh.insert_vertex((0.0, 0.0));
+            let b = mesh.insert_vertex((-100.0, 100.0));
+            let c = mesh.insert_vertex((100.0, 0.0));
+            let infinity = mesh.insert_face(VoronoiVertex::Infinite);
+            let inside = mesh.insert_face(VoronoiVertex::Finite(0.0, 0.0));
+            let e1 = mesh.make_edge(a, b, infinity, inside);
+            let e2 = mesh.connect_vertex(e1, c);
+            mesh.connect_primal(e2, e1);
+            mesh
+        };
+        let (mut stepped, mut direct) = (triangle(), triangle());
+
+        let mut rng: rand::rngs::StdRng = rand::SeedableRng::seed_from_u64(3);
+        let mut kinds = Vec::new();
+        for _ in 0..20 {
+            let x = (rng.gen_range(-200.0..500.0), rng.gen_range(-300.0..300.0)).into();
+            direct.insert_delaunay_vertex(x);
+            let mut insertion = stepped.insertion(x);
+            let mut steps = Vec::new();
+            while let Some(step) = insertion.step(&mut stepped) {
+                steps.push(step);
+            }
+            assert!(insertion.is_done());
+            let inserted = insertion.vertex().map(InsertionStep::Inserted);
+            assert_eq!(steps.last().copied(), inserted);
+            kinds.extend(steps.iter().map(std::mem::discriminant));
+        }
+        assert_eq!(stepped.validate(), Ok(()));
+        assert_eq!(
+            format!("{:?}", stepped.quad_edges),
+            format!("{:?}", direct.quad_edges)
+        );
+        for step in [
+            InsertionStep::Walk(PrimalDEdgeEntity(0, 0)),
+            InsertionStep::EdgeFound(PrimalDEdgeEntity(0, 0)),
+            InsertionStep::FanEdgeAdded(PrimalDEdgeEntity(0, 0)),
+            InsertionStep::EdgeFlipped(PrimalDEdgeEntity(0, 0)),
+        ] {
+            assert!(kinds.contains(&std::mem::discriminant(&step)));
+        }
+
+        // an existing vertex is found and left alone
+        let mut insertion = stepped.insertion((0.0, 0.0).into());
+        let last = std::iter::from_fn(|| insertion.step(&mut stepped)).last();
+        let existing = insertion.vertex().unwrap();
+        assert_eq!(last, Some(InsertionStep::Coincident(existing)));
+        assert_eq!(*stepped.get_vertex(existing), (0.0, 0.0).into());
+        assert_eq!(stepped.vertices.live_count(), 23);
+    }
+
+    #[test]
+    fn degenerate_insertions() {
+        use rand::Rng;
+
+        fn check(mesh: &DelaunayMesh) {
+            assert_eq!(mesh.validate(), Ok(()));
+            assert!(mesh.edges().all(|e| mesh.is_delaunay(e)));
+            for (face, boundary) in mesh.face_boundaries() {
+                if !mesh.get_face(face).is_infinite() {
+                    let corners =
+                        boundary.iter().map(|&e| *mesh.primal(e).org()).collect::<Vec<_>>();
+                    assert_eq!(corners.len(), 3);
+                    assert!(geometry::ccw(corners[0], corners[1], corners[2]));
+                }
+            }
+        }
+
+        // a chain along a line, grown at both ends and split in the middle
+        let mut mesh = DelaunayMesh::new();
+        let first = mesh.insert_delaunay_vertex((0.0, 0.0).into());
+        for p in [(2.0, 2.0), (1.0, 1.0), (-1.0, -1.0), (3.0, 3.0)] {
+            mesh.insert_delaunay_vertex(p.into());
+            check(&mesh);
+        }
+        assert_eq!(mesh.insert_delaunay_vertex((0.0, 0.0).into()), first);
+        assert_eq!(mesh.vertices.live_count(), 5);
+        assert_eq!(mesh.edges().count(), 4);
+        assert_eq!(mesh.faces.live_count(), 1);
+
+        // the first point off the line fans the chain into triangles
+        mesh.insert_delaunay_vertex((0.0, 2.0).into());
+        check(&mesh);
+        assert_eq!(mesh.faces.live_count(), 1 + 4);
+
+        // points on an inner edge, on a hull edge and in line with a hull edge
+        let mut mesh = DelaunayMesh::new();
+        for p in [(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)] {
+            mesh.insert_delaunay_vertex(p.into());
+        }
+        let center = mesh.insert_delaunay_vertex((1.0, 1.0).into());
+        check(&mesh);
+        assert_eq!(mesh.faces.live_count(), 1 + 4);
+        assert_eq!(mesh.vertex_stars().find(|(v, _)| *v == center).unwrap().1.len(), 4);
+        mesh.insert_delaunay_vertex((1.0, 0.0).into());
+        check(&mesh);
+        assert_eq!(mesh.faces.live_count(), 1 + 5);
+        mesh.insert_delaunay_vertex((3.0, 0.0).into());
+        check(&mesh);
+        assert_eq!(mesh.faces.live_count(), 1 + 6);
+
+        // a small grid is full of all three
+        let mut rng: rand::rngs::StdRng = rand::SeedableRng::seed_from_u64(7);
+        let mut mesh = DelaunayMesh::new();
+        let mut distinct = std::collections::HashSet::new();
+        for _ in 0..200 {
+            let (x, y) = (rng.gen_range(0..7), rng.gen_range(0..7));
+            let v = mesh.insert_delaunay_vertex((x as f32, y as f32).into());
+            assert_eq!(*mesh.get_vertex(v), (x as f32, y as f32).into());
+            distinct.insert((x, y));
+            check(&mesh);
+        }
+        assert_eq!(mesh.vertices.live_count(), distinct.len());
+    }
+
+    #[test]
+    fn faces_store_their_circumcenters() {
+        use cgmath::InnerSpace;
+        use rand::Rng;
+
+        let mut mesh = DelaunayMesh::new();
+        let a = mesh.insert_vertex((0.0, 0.0));
+        let b = mesh.insert_vertex((-100.0, 100.0));
+        let c = mesh.insert_vertex((100.0, 0.0));
+        let infinity = mesh.insert_face(VoronoiVertex::Infinite);
+        let inside = mesh.insert_face(VoronoiVertex::Finite(0.0, 0.0));
+        let e1 = mesh.make_edge(a, b, infinity, inside);
+        let e2 = mesh.connect_vertex(e1, c);
+        mesh.connect_primal(e2, e1);
+        mesh.update_voronoi_vertices();
+
+        // the stored position is the exact circumcenter of the face's corners, to rounding
+        let check = |mesh: &DelaunayMesh| {
+            for (face, boundary) in mesh.face_boundaries() {
+                let stored = match mesh.get_face(face).position() {
+                    Some(stored) => stored.cast::<f64>().unwrap(),
+                    None => continue,
+                };
+                let corners = boundary
+                    .iter()
+                    .map(|&e| mesh.primal(e).org().cast::<f64>().unwrap())
+                    .collect::<Vec<_>>();
+                assert_eq!(corners.len(), 3, "{:?} is not a triangle", face);
+                let center = geometry::circumcenter(corners[0], corners[1], corners[2]);
+                let radius = (corners[0] - center).magnitude();
+                assert!(
+                    (stored - center).magnitude() < 1e-4 * radius,
+                    "{:?} stores {:?}, not {:?}",
+                    face,
+                    stored,
+                    center
+                );
+            }
+        };
+        check(&mesh);
+        assert_eq!(mesh.get_face(inside).position(), Some((50.0, 150.0).into()));
+
+        let mut rng: rand::rngs::StdRng = rand::SeedableRng::seed_from_u64(3);
+        for _ in 0..20 {
+            mesh.insert_delaunay_vertex(
+                (rng.gen_range(-200.0..500.0), rng.gen_range(-300.0..300.0)).into(),
+            );
+            check(&mesh);
+        }
+        // more seeds, and a lattice where most insertions flip nothing
+        for seed in 4..8 {
+            let mut rng: rand::rngs::StdRng = rand::SeedableRng::seed_from_u64(seed);
+            let mut random = DelaunayMesh::new();
+            for _ in 0..40 {
+                random.insert_delaunay_vertex(
+                    (rng.gen_range(-20.0..20.0), rng.gen_range(-20.0..20.0)).into(),
+                );
+                check(&random);
+            }
+        }
+        let mut lattice = DelaunayMesh::new();
+        for x in 0..6 {
+            for y in 0..6 {
+                lattice.insert_delaunay_vertex((x as f32 * 3.0, y as f32 * 2.0).into());
+                check(&lattice);
+            }
+        }
+
+        // swaps, undo and compaction bring the circumcenters up to date by themselves
+        let mut history = History::default();
+        let diagonal = mesh
+            .edges()
+            .find(|&e| {
+                let e = mesh.primal(e);
+                !e.left().is_infinite() && !e.sym().left().is_infinite()
+            })
+            .unwrap();
+        mesh.begin();
+        mesh.swap_primal(diagonal);
+        history.push(mesh.commit());
+        check(&mesh);
+        history.undo(&mut mesh);
+        check(&mesh);
+        mesh.swap_primal(diagonal);
+        mesh.compact();
+        check(&mesh);
+    }
+
+    #[test]
+    fn voronoi_cells_tile_the_clip_region() {
+        use cgmath::InnerSpace;
+        use rand::Rng;
+
+        let mut mesh = DelaunayMesh::new();
+        let a = mesh.insert_vertex((0.0, 0.0));
+        let b = mesh.insert_vertex((-100.0, 100.0));
+        let c = mesh.insert_vertex((100.0, 0.0));
+        let infinity = mesh.insert_face(VoronoiVertex::Infinite);
+        let inside = mesh.insert_face(VoronoiVertex::Finite(0.0, 0.0));
+        let e1 = mesh.make_edge(a, b, infinity, inside);
+        let e2 = mesh.connect_vertex(e1, c);
+        mesh.connect_primal(e2, e1);
+        for p in [(0.0, 30.0), (200.0, 150.0), (-50.0, -80.0)] {
+            mesh.insert_delaunay_vertex(p.into());
+        }
+
+        let clip = clip_rectangle((-1000.0, -1000.0).into(), (1000.0, 1000.0).into());
+        let cells = mesh.voronoi_cells(&clip).collect::<Vec<_>>();
+        assert_eq!(cells.len(), 6);
+        let total = cells.iter().map(|cell| cell.area()).sum::<f32>();
+        assert!((total - 4.0e6).abs() < 1.0, "{}", total);
+
+        let middle = cells.iter().find(|cell| cell.site == VertexEntity(3, 0)).unwrap();
+        assert!(!middle.unbounded);
+        assert!(middle.neighbors.len() >= 3);
+        // a bounded cell clipped by nothing has the circumcenters around the site as corners
+        let centers = mesh
+            .faces
+            .iter()
+            .filter_map(|(_, _, face)| face.position())
+            .collect::<Vec<_>>();
+        assert!(middle
+            .polygon
+            .iter()
+            .all(|corner| centers.iter().any(|c| (corner - c).magnitude() < 1e-2)));
+        for cell in &cells {
+            let site = *mesh.get_vertex(cell.site);
+            for corner in &cell.polygon {
+                let distance = (corner - site).magnitude();
+                for &neighbor in &cell.neighbors {
+                    let other = (corner - *mesh.get_vertex(neighbor)).magnitude();
+                    assert!(distance <= other + 1e-2);
+                }
+            }
+        }
+        assert!(cells.iter().filter(|cell| cell.unbounded).count() >= 3);
+
+        // a clip region inside one cell is all of that cell, with none of its edges
+        let small = clip_rectangle((-2.0, 28.0).into(), (2.0, 32.0).into());
+        let spoke = mesh
+            .primal_dedges()
+            .find(|&e| mesh.get_primal(e).org == middle.site)
+            .unwrap();
+        assert_eq!(middle.neighbors.len(), mesh.get_primal_onext_ring(spoke).count());
+        let inside = mesh.voronoi_cell(spoke, &small);
+        assert!((inside.area() - 16.0).abs() < 1e-3);
+        assert!(inside.neighbors.is_empty());
+
+        // the rays of hull sites reach far enough to cover any clip region
+        let mut rng: rand::rngs::StdRng = rand::SeedableRng::seed_from_u64(9);
+        let points = (0..30).map(|_| (rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)).into());
+        let mesh = DelaunayMesh::<f64>::from_points(points.collect::<Vec<_>>());
+        let clip = clip_rectangle((-50.0, -50.0).into(), (50.0, 50.0).into());
+        let total = mesh.voronoi_cells(&clip).map(|cell| cell.area()).sum::<f64>();
+        assert!((total - 1.0e4).abs() < 1e-6, "{}", total);
+    }
+
+    #[test]
+    fn divide_and_conquer_triangulation() {
+        use rand::Rng;
+
+        let mut rng: rand::rngs::StdRng = rand::SeedableRng::seed_from_u64(5);
+        let points = (0..1000)
+            .map(|_| (rng.gen_range(-500.0..500.0), rng.gen_range(-500.0..500.0)).into())
+            .collect::<Vec<_>>();
+        let mesh = DelaunayMesh::from_points(points.iter().copied().chain([points[7]]));
+        assert_eq!(mesh.validate(), Ok(()));
+        assert_eq!(mesh.vertices.live_count(), 1000);
+        assert!(mesh.edges().all(|e| mesh.is_delaunay(e)));
+        // Euler's formula, counting the infinite face
+        assert_eq!(1000 + mesh.faces.live_count(), mesh.edges().count() + 2);
+        let infinite = mesh
+            .faces
+            .iter()
+            .filter(|(_, _, face)| face.is_infinite())
+            .count();
+        assert_eq!(infinite, 1);
+        for (face, boundary) in mesh.face_boundaries() {
+            assert!(mesh.get_face(face).is_infinite() || boundary.len() == 3);
+        }
+
+        let line = DelaunayMesh::from_points((0..5).map(|i| (i as f32, 2.0 * i as f32).into()));
+        assert_eq!(line.validate(), Ok(()));
+        assert_eq!(line.edges().count(), 4);
+        assert_eq!(line.faces.live_count(), 1);
+        assert_eq!(DelaunayMesh::from_points([(1.0, 1.0).into()]).edges().count(), 0);
+    }
+
+    #[test]
+    fn predicates_are_exact_near_degeneracy() {
+        use cgmath::Point2;
+
+        // every coordinate used is a multiple of 2^-24, small enough for exact i128 arithmetic
+        let fixed = |p: Point2<f32>| ((p.x * 16777216.0) as i128, (p.y * 16777216.0) as i128);
+        let orient = |a, b, c| {
+            let ((ax, ay), (bx, by), (cx, cy)) = (fixed(a), fixed(b), fixed(c));
+            ((ax - cx) * (by - cy) - (ay - cy) * (bx - cx)).signum()
+        };
+        let incircle = |a, b, c, d| {
+            let [(ax, ay), (bx, by), (cx, cy)] = [a, b, c].map(|p| {
+                let ((x, y), (dx, dy)) = (fixed(p), fixed(d));
+                (x - dx, y - dy)
+            });
+            let lift = |x: i128, y: i128| x * x + y * y;
+            (lift(ax, ay) * (bx * cy - by * cx) - lift(bx, by) * (ax * cy - ay * cx)
+                + lift(cx, cy) * (ax * by - ay * bx))
+                .signum()
+        };
+        let sign = |x: f64| x.partial_cmp(&0.0).unwrap() as i128;
+
+        // Shewchuk's grid of points next to the line through (12, 12) and (24, 24)
+        let ulp = f32::EPSILON / 2.0;
+        let (b, c) = (Point2::new(12.0, 12.0), Point2::new(24.0, 24.0));
+        let mut collinear = 0;
+        for i in 0..64 {
+            for j in 0..64 {
+                let a = Point2::new(0.5 + i as f32 * ulp, 0.5 + j as f32 * ulp);
+                assert_eq!(sign(predicates::orient2d(a, b, c)), orient(a, b, c));
+                assert_eq!(geometry::ccw(a, b, c), orient(a, b, c) > 0);
+                collinear += (orient(a, b, c) == 0) as usize;
+            }
+        }
+        assert_eq!(collinear, 64);
+
+        // points on and just off the circle of radius 5 through (5, 0), (0, 5) and (-5, 0)
+        let (a, b, c) = (Point2::new(5.0, 0.0), Point2::new(0.0, 5.0), Point2::new(-5.0, 0.0));
+        let ulp = 4.0 * f32::EPSILON;
+        for (x, y) in [(3.0, 4.0), (4.0, -3.0), (-3.0, -4.0)] {
+            for (i, j) in [(0.0, 0.0), (1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (1.0, -1.0)] {
+                let d = Point2::new(x + i * ulp, y + j * ulp);
+                assert_eq!(sign(predicates::incircle(a, b, c, d)), incircle(a, b, c, d));
+                if incircle(a, b, c, d) != 0 {
+                    assert_eq!(geometry::in_circle(a, b, c, d), incircle(a, b, c, d) > 0);
+                }
+            }
+            assert_eq!(predicates::incircle(a, b, c, Point2::new(x, y)), 0.0);
+        }
+
+        // a lattice, where every square is cocircular, and a run of points along its diagonal
+        let lattice = (0..20).flat_map(|i| (0..20).map(move |j| (i as f32, j as f32)));
+        let diagonal = (1..200).map(|i| (0.5 + i as f32 * 1e-7, 0.5 + i as f32 * 1e-7));
+        let mut mesh = DelaunayMesh::from_points(lattice.chain(diagonal).map(Into::into));
+        assert_eq!(mesh.validate(), Ok(()));
+        assert!(mesh.edges().all(|e| mesh.is_delaunay(e)));
+        for i in 1..50 {
+            mesh.insert_delaunay_vertex((10.25 + i as f32 * 1e-6, 10.25 - i as f32 * 1e-6).into());
+        }
+        assert_eq!(mesh.validate(), Ok(()));
+        assert!(mesh.edges().all(|e| mesh.is_delaunay(e)));
+    }
+
+    #[test]
+    fn cocircular_points_triangulate_the_same_in_any_order() {
+        use cgmath::Point2;
+        use rand::seq::SliceRandom;
+
+        // exactly one diagonal of a square is Delaunay, whichever corner comes first
+        let square = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)].map(Point2::from);
+        for turn in 0..4 {
+            let [p, q, r, s] = [0, 1, 2, 3].map(|i| square[(i + turn) % 4]);
+            let pr = !geometry::in_circle(s, p, r, q);
+            let qs = !geometry::in_circle(p, q, s, r);
+            assert_ne!(pr, qs);
+            assert_eq!(pr, turn % 2 == 0);
+        }
+
+        let edge_set = |mesh: &DelaunayMesh| {
+            let mut edges = mesh
+                .edges()
+                .map(|e| {
+                    let e = mesh.primal(e);
+                    let [a, b] = [*e.org(), *e.dest()].map(|p| (p.x as i32, p.y as i32));
+                    (a.min(b), a.max(b))
+                })
+                .collect::<Vec<_>>();
+            edges.sort();
+            edges
+        };
+        let mut lattice = (0..6)
+            .flat_map(|x| (0..6).map(move |y| Point2::new(x as f32, y as f32)))
+            .collect::<Vec<_>>();
+        let expected = edge_set(&DelaunayMesh::from_points(lattice.iter().copied()));
+        let mut rng: rand::rngs::StdRng = rand::SeedableRng::seed_from_u64(8);
+        for _ in 0..5 {
+            lattice.shuffle(&mut rng);
+            let mut mesh = DelaunayMesh::new();
+            for &p in &lattice {
+                mesh.insert_delaunay_vertex(p);
+            }
+            assert_eq!(mesh.validate(), Ok(()));
+            assert_eq!(edge_set(&mesh), expected);
+        }
+    }
+
+    #[test]
+    fn double_precision_delaunay_mesh() {
+        use cgmath::{MetricSpace, Point2};
+        use rand::Rng;
+
+        // centimetres on a grid far from the origin, which f32 can't tell apart
+        let mut rng: rand::rngs::StdRng = rand::SeedableRng::seed_from_u64(6);
+        let origin = (4_500_000.0, 5_200_000.0);
+        let survey = (0..300)
+            .map(|_| {
+                let (x, y) = (rng.gen_range(0..300), rng.gen_range(0..300));
+                Point2::new(origin.0 + x as f64 * 0.01, origin.1 + y as f64 * 0.01)
+            })
+            .collect::<Vec<_>>();
+        let mut mesh: DelaunayMesh<f64> = DelaunayMesh::from_points(survey.iter().copied());
+        assert_eq!(mesh.validate(), Ok(()));
+        assert!(mesh.vertices.live_count() > 290);
+        assert!(mesh.edges().all(|e| mesh.is_delaunay(e)));
+
+        mesh.insert_delaunay_vertex(Point2::new(origin.0 + 1.505, origin.1 + 1.505));
+        assert_eq!(mesh.validate(), Ok(()));
+        assert!(mesh.edges().all(|e| mesh.is_delaunay(e)));
+        for (face, boundary) in mesh.face_boundaries() {
+            let Some(center) = mesh.get_face(face).position() else {
+                continue;
+            };
+            let radius = center.distance(*mesh.primal(boundary[0]).org());
+            for e in &boundary[1..] {
+                assert!((center.distance(*mesh.primal(*e).org()) - radius).abs() < 1e-6);
+            }
+        }
+
+        let narrow = DelaunayMesh::from_points(survey.iter().map(|p| p.cast::<f32>().unwrap()));
+        assert!(narrow.vertices.live_count() < mesh.vertices.live_count() / 2);
+    }
+
+    #[test]
+    fn integer_lattice_delaunay_mesh() {
+        use std::cmp::Ordering;
+
+        use cgmath::Point2;
+        use geometry::Scalar;
+        use rand::{seq::SliceRandom, Rng};
+
+        // f64 rounds 2^62 - 1 to 2^62, which would make these collinear
+        let big = 1_i64 << 62;
+        let (a, b) = (Point2::new(0, 0), Point2::new(big, big - 1));
+        let c = Point2::new(big / 2, big / 2);
+        assert!(geometry::ccw(a, b, c));
+        assert!(!geometry::ccw(a, c, b));
+        let square = [(0, 0), (2, 0), (2, 2), (0, 2)].map(|(x, y)| Point2::new(big + x, y - big));
+        let [p, q, r, s] = square;
+        assert_eq!(i64::incircle_sign(p, q, r, s), Ordering::Equal);
+        assert!(geometry::in_circle(p, q, r, Point2::new(big + 1, 1 - big)));
+        assert!(!geometry::in_circle(p, q, r, Point2::new(big + 1, 3 - big)));
+
+        // a small lattice far out, with repeats and cocircular quads everywhere
+        let mut rng: rand::rngs::StdRng = rand::SeedableRng::seed_from_u64(9);
+        let mut points = (0..300)
+            .map(|_| Point2::new(big + rng.gen_range(0..16), rng.gen_range(0..16) - big))
+            .collect::<Vec<_>>();
+        let edge_set = |mesh: &DelaunayMesh<i64>| {
+            let mut edges = mesh
+                .edges()
+                .map(|e| {
+                    let e = mesh.primal(e);
+                    let (a, b) = (*e.org(), *e.dest());
+                    ((a.x, a.y).min((b.x, b.y)), (a.x, a.y).max((b.x, b.y)))
+                })
+                .collect::<Vec<_>>();
+            edges.sort();
+            edges
+        };
+        let mesh = DelaunayMesh::from_points(points.iter().copied());
+        assert_eq!(mesh.validate(), Ok(()));
+        assert!(mesh.edges().all(|e| mesh.is_delaunay(e)));
+        let mut distinct = points.clone();
+        distinct.sort_by(|p, q| geometry::lexicographic_order(*p, *q));
+        distinct.dedup();
+        assert_eq!(mesh.vertices.live_count(), distinct.len());
+        // circumcenters come out in f64, as near the lattice as it can hold
+        for (face, _) in mesh.face_boundaries() {
+            if let Some(center) = mesh.get_face(face).position() {
+                assert!((center.x - big as f64).abs() < 1e4);
+                assert!((center.y + big as f64).abs() < 1e4);
+            }
+        }
+
+        points.shuffle(&mut rng);
+        let mut incremental = DelaunayMesh::new();
+        for &p in &points {
+            incremental.insert_delaunay_vertex(p);
+        }
+        assert_eq!(incremental.validate(), Ok(()));
+        assert_eq!(edge_set(&incremental), edge_set(&mesh));
+
+        // the full i32 range, whose incircle determinant overflows i128
+        let corners = [(i32::MIN, i32::MIN), (i32::MAX, i32::MIN), (i32::MAX, i32::MAX)];
+        let spread = (0..200)
+            .map(|_| Point2::new(rng.gen::<i32>(), rng.gen::<i32>()))
+            .chain(corners.map(Point2::from))
+            .collect::<Vec<_>>();
+        let mesh: DelaunayMesh<i32> = DelaunayMesh::from_points(spread);
+        assert_eq!(mesh.validate(), Ok(()));
+        assert!(mesh.edges().all(|e| mesh.is_delaunay(e)));
+    }
+
+    #[test]
+    fn locate_point_in_empty_mesh() {
+        let mut mesh = DelaunayMesh::new();
+        mesh.insert_vertex((0.0, 1.0));
+        assert_eq!(
+            mesh.try_locate_point((0.0, 0.0).into()),
+            Err(MeshError::EmptyMesh)
+        );
+    }
+
+    #[test]
+    fn validate_random_delaunay() {
+        use rand::Rng;
+
+        let mut mesh = DelaunayMesh::new();
+        let a = mesh.insert_vertex((-100.0, -100.0));
+        let b = mesh.insert_vertex((-100.0, 100.0));
+        let c = mesh.insert_vertex((100.0, 0.0));
+        let infinity = mesh.insert_face(VoronoiVertex::Infinite);
+        let inside = mesh.insert_face(VoronoiVertex::Finite(0.0, 0.0));
+
+        let e1 = mesh.make_edge(a, b, infinity, inside);
+        let e2 = mesh.connect_vertex(e1, c);
+        mesh.connect_primal(e2, e1);
+        assert_eq!(mesh.validate_planar(), Ok(()));
+
+        let mut rng: rand::rngs::StdRng = rand::SeedableRng::seed_from_u64(0);
+        for _ in 0..50 {
+            mesh.insert_delaunay_vertex(
+                (rng.gen_range(-200.0..500.0), rng.gen_range(-300.0..300.0)).into(),
+            );
+            assert_eq!(mesh.validate_planar(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn validate_reports_corrupt_face_label() {
+        let mut mesh = TopologicalMesh::new();
+        let a = mesh.insert_vertex("A");
+        let b = mesh.insert_vertex("B");
+        let c = mesh.insert_vertex("C");
+        let infinity = mesh.insert_face("(infinity)");
+        let inside = mesh.insert_face("(inside)");
+
+        let e1 = mesh.make_edge(a, b, inside, infinity);
+        let e2 = mesh.connect_vertex(e1, c);
+        mesh.connect_primal(e2, e1);
+        assert_eq!(mesh.validate(), Ok(()));
+
+        mesh.get_dual_mut(e2.rot_inv()).org = infinity;
+        let violations = mesh.validate().unwrap_err();
+        assert!(violations.contains(&Violation::FaceLabelMismatch {
+            dedge: e2.rot_inv(),
+            face: infinity,
+            onext_face: inside,
+        }));
+    }
+
+    #[test]
+    fn validate_accepts_a_torus() {
+        // a 3x3 grid of quads with opposite sides glued together
+        let at = |i: usize, j: usize| (i % 3) * 3 + j % 3;
+        let quads = (0..3)
+            .flat_map(|i| {
+                (0..3).map(move |j| [at(i, j), at(i + 1, j), at(i + 1, j + 1), at(i, j + 1)])
+            })
+            .collect::<Vec<_>>();
+        let torus = TopologicalMesh::from_polygons(0..9, &quads, |_| 0).unwrap();
+        assert_eq!(torus.validate(), Ok(()));
+        // V - E + F = 9 - 18 + 9 = 0, not 2
+        assert_eq!(
+            torus.validate_planar(),
+            Err(vec![Violation::EulerCharacteristic {
+                vertices: 9,
+                edges: 18,
+                faces: 9,
+                components: 1,
+            }])
+        );
+    }
 }
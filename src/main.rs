@@ -1,6 +1,6 @@
 // use std::fmt::Debug;
 
-use quad_edge::delaunay_voronoi::{DelaunayMesh, VoronoiVertex};
+use quad_edge::delaunay_voronoi::DelaunayMesh;
 use rand;
 
 use std::env;
@@ -11,25 +11,13 @@ mod gui;
 fn main() {
     let args: Vec<_> = env::args().collect();
 
-    let mut mesh = DelaunayMesh::new();
-    let a = mesh.insert_vertex((-100.0, -100.0));
-    let b = mesh.insert_vertex((-100.0, 100.0));
-    let c = mesh.insert_vertex((100.0, 0.0));
-    let infinity = mesh.insert_face(VoronoiVertex::Infinite);
-    let inside = mesh.insert_face(VoronoiVertex::Finite(0.0, 0.0));
-
-    let e1 = mesh.make_edge(a, b, infinity, inside);
-    let e2 = mesh.connect_vertex(e1, c);
-    let _e3 = mesh.connect_primal(e2, e1);
-
     use rand::Rng;
 
     let mut rng: rand::rngs::StdRng = rand::SeedableRng::seed_from_u64(args[1].parse().unwrap_or(0));
-    for _ in 0..args[2].parse().unwrap_or(10) {
-        mesh.insert_delaunay_vertex(
-            (rng.gen_range(-200.0..500.0), rng.gen_range(-300.0..300.0)).into(),
-        );
-    }
+    let count = args[2].parse().unwrap_or(10);
+    let random = (0..count).map(|_| (rng.gen_range(-200.0..500.0), rng.gen_range(-300.0..300.0)));
+    #[cfg_attr(not(feature = "gui"), allow(unused_variables))]
+    let mesh = DelaunayMesh::from_points(random.map(Into::into));
     #[cfg(feature = "gui")]
     gui::explore_mesh(mesh);
 }
@@ -0,0 +1,74 @@
+use super::{
+    quad::{DualDEdgeEntity, FaceEntity, PrimalDEdgeEntity, VertexEntity},
+    Mesh, MeshExtension,
+};
+
+/// An edit reported by the change log, see [`Mesh::start_change_log`].
+///
+/// Compound edits report the primitives they are made of, followed by themselves: a
+/// [`Mesh::delete_primal`] is two splices and then [`MeshChange::EdgeDeleted`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshChange {
+    EdgeCreated(PrimalDEdgeEntity),
+    /// The handle is stale by the time it is reported.
+    EdgeDeleted(PrimalDEdgeEntity),
+    /// [`Mesh::splice_primal`] of the two dedges, or the matching [`Mesh::splice_dual`].
+    Spliced(PrimalDEdgeEntity, PrimalDEdgeEntity),
+    /// [`Mesh::swap_primal`]. Both endpoints and both faces of the edge changed.
+    Swapped(PrimalDEdgeEntity),
+    /// Borrowed through [`Mesh::get_primal_mut`], so its origin or onext may have changed.
+    PrimalChanged(PrimalDEdgeEntity),
+    /// Borrowed through [`Mesh::get_dual_mut`], so its origin or onext may have changed.
+    DualChanged(DualDEdgeEntity),
+    /// [`Mesh::set_dual_org`] gave the dedge a different face, reported once it has.
+    FaceRelabelled(DualDEdgeEntity),
+    /// Its payload was borrowed through [`Mesh::get_edge_mut`].
+    EdgeChanged(PrimalDEdgeEntity),
+    VertexInserted(VertexEntity),
+    /// Borrowed through [`Mesh::get_vertex_mut`], usually to move it.
+    VertexMoved(VertexEntity),
+    VertexDeleted(VertexEntity),
+    FaceInserted(FaceEntity),
+    /// Borrowed through [`Mesh::get_face_mut`].
+    FaceChanged(FaceEntity),
+    FaceDeleted(FaceEntity),
+    /// Anything may have changed, after [`Mesh::revert`] or [`Mesh::compact`]. Earlier
+    /// changes in the log refer to entities that may no longer mean the same thing.
+    Reset,
+}
+
+impl<V, F, E, Cache: MeshExtension<V, F, E>> Mesh<V, F, E, Cache> {
+    /// Start logging changes for [`Mesh::drain_changes`]. Nothing is logged until this is
+    /// called, and calling it again keeps what was logged so far.
+    pub fn start_change_log(&mut self) {
+        self.changes.get_or_insert_with(Vec::new);
+    }
+
+    /// Stop logging and drop the changes not yet drained.
+    pub fn stop_change_log(&mut self) {
+        self.changes = None;
+    }
+
+    /// The changes logged since the last drain, oldest first.
+    pub fn drain_changes(&mut self) -> impl Iterator<Item = MeshChange> + '_ {
+        self.changes
+            .iter_mut()
+            .flat_map(|changes| changes.drain(..))
+    }
+
+    /// Pass `change` on to the extension and log it.
+    pub(super) fn notify(&mut self, change: MeshChange) {
+        match change {
+            MeshChange::EdgeCreated(e) => self.cache.edge_created(e),
+            MeshChange::EdgeDeleted(e) => self.cache.edge_deleted(e),
+            MeshChange::Spliced(a, b) => self.cache.spliced(a, b),
+            MeshChange::Swapped(e) => self.cache.swapped(e),
+            MeshChange::FaceRelabelled(e) => self.cache.face_relabelled(e),
+            MeshChange::Reset => self.cache.reset(),
+            _ => (),
+        }
+        if let Some(changes) = &mut self.changes {
+            changes.push(change);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use super::{
+    quad::{DualDEdgeEntity, FaceEntity, PrimalDEdgeEntity, VertexEntity},
+    Mesh, MeshChange, MeshExtension, SlotRemap,
+};
+
+/// Translates entities from before a [`Mesh::compact`] to after it.
+#[derive(Debug, Clone, Default)]
+pub struct MeshRemap {
+    quad_edges: SlotRemap,
+    vertices: SlotRemap,
+    faces: SlotRemap,
+}
+
+impl MeshRemap {
+    /// `None` if `e` was already stale or dangling before compacting.
+    pub fn primal(&self, e: PrimalDEdgeEntity) -> Option<PrimalDEdgeEntity> {
+        self.quad_edges
+            .get(e.edge(), e.1)
+            .map(|(edge, generation)| PrimalDEdgeEntity((edge << 1) | (e.0 & 1), generation))
+    }
+    /// `None` if `e` was already stale or dangling before compacting.
+    pub fn dual(&self, e: DualDEdgeEntity) -> Option<DualDEdgeEntity> {
+        self.quad_edges
+            .get(e.edge(), e.1)
+            .map(|(edge, generation)| DualDEdgeEntity((edge << 1) | (e.0 & 1), generation))
+    }
+    /// `None` if `v` was already stale or dangling before compacting.
+    pub fn vertex(&self, v: VertexEntity) -> Option<VertexEntity> {
+        self.vertices
+            .get(v.0, v.1)
+            .map(|(index, generation)| VertexEntity(index, generation))
+    }
+    /// `None` if `f` was already stale or dangling before compacting.
+    pub fn face(&self, f: FaceEntity) -> Option<FaceEntity> {
+        self.faces
+            .get(f.0, f.1)
+            .map(|(index, generation)| FaceEntity(index, generation))
+    }
+}
+
+impl<V, F, E, Cache: MeshExtension<V, F, E>> Mesh<V, F, E, Cache> {
+    /// Rewrite the quad-edge, vertex and face storage without holes left by deletions.
+    ///
+    /// Every `org` and `onext` is translated in place. All other handles into the mesh must be
+    /// translated with the returned [`MeshRemap`], and the extension is reset since it can't be.
+    /// [`MeshChange::Reset`] is logged. References that were already stale end up dangling,
+    /// where [`Mesh::validate`] still reports them.
+    ///
+    /// Panics while a transaction is being recorded, since it couldn't be reverted.
+    pub fn compact(&mut self) -> MeshRemap {
+        let remap = MeshRemap {
+            quad_edges: self.quad_edges.compact(),
+            vertices: self.vertices.compact(),
+            faces: self.faces.compact(),
+        };
+        // edge payloads share the quad-edges' slots, so they are remapped the same way
+        self.edge_data.compact();
+
+        for (_, _, quad_edge) in self.quad_edges.iter_mut() {
+            for primal in &mut quad_edge.primal {
+                primal.org = remap
+                    .vertex(primal.org)
+                    .unwrap_or(VertexEntity(usize::MAX, 0));
+                primal.onext = remap
+                    .primal(primal.onext)
+                    .unwrap_or(PrimalDEdgeEntity(usize::MAX, 0));
+            }
+            for dual in &mut quad_edge.dual {
+                dual.org = remap.face(dual.org).unwrap_or(FaceEntity(usize::MAX, 0));
+                dual.onext = remap
+                    .dual(dual.onext)
+                    .unwrap_or(DualDEdgeEntity(usize::MAX, 0));
+            }
+        }
+        self.notify(MeshChange::Reset);
+        Cache::settle(self);
+
+        remap
+    }
+}
@@ -0,0 +1,77 @@
+/// Quad-edge navigation shared by [`super::PrimalMeshCursor`] and [`super::DualMeshCursor`].
+///
+/// Implementors only provide `onext`, `sym` and the `rot`/`rot_inv` crossovers to the other
+/// graph. Every other operator of Guibas & Stolfi's edge algebra is derived from those, so
+/// generic traversal code runs over the primal and dual graphs alike.
+pub trait MeshCursor: Sized {
+    type Entity: Copy + PartialEq;
+    /// The cursor type of the dual graph, whose dual is `Self` again.
+    type Other: MeshCursor<Other = Self>;
+
+    /// Drop reference to mesh.
+    fn id(&self) -> Self::Entity;
+
+    fn onext(&self) -> Self;
+    fn sym(&self) -> Self;
+    fn rot(&self) -> Self::Other;
+    fn rot_inv(&self) -> Self::Other;
+
+    fn oprev(&self) -> Self {
+        self.rot().onext().rot()
+    }
+    fn dnext(&self) -> Self {
+        self.sym().onext().sym()
+    }
+    fn dprev(&self) -> Self {
+        self.rot_inv().onext().rot_inv()
+    }
+    fn lnext(&self) -> Self {
+        self.rot_inv().onext().rot()
+    }
+    fn lprev(&self) -> Self {
+        self.onext().sym()
+    }
+    fn rnext(&self) -> Self {
+        self.rot().onext().rot_inv()
+    }
+    fn rprev(&self) -> Self {
+        self.sym().onext()
+    }
+
+    fn onext_mut(&mut self) -> &mut Self {
+        *self = self.onext();
+        self
+    }
+    fn sym_mut(&mut self) -> &mut Self {
+        *self = self.sym();
+        self
+    }
+    fn oprev_mut(&mut self) -> &mut Self {
+        *self = self.oprev();
+        self
+    }
+    fn dnext_mut(&mut self) -> &mut Self {
+        *self = self.dnext();
+        self
+    }
+    fn dprev_mut(&mut self) -> &mut Self {
+        *self = self.dprev();
+        self
+    }
+    fn lnext_mut(&mut self) -> &mut Self {
+        *self = self.lnext();
+        self
+    }
+    fn lprev_mut(&mut self) -> &mut Self {
+        *self = self.lprev();
+        self
+    }
+    fn rnext_mut(&mut self) -> &mut Self {
+        *self = self.rnext();
+        self
+    }
+    fn rprev_mut(&mut self) -> &mut Self {
+        *self = self.rprev();
+        self
+    }
+}
@@ -1,67 +1,73 @@
-use std::cell::RefCell;
-
 use super::{
-    quad::{DualDEdgeEntity, DualDirectedEdge, PrimalDEdgeEntity},
-    Mesh, PrimalMeshCursor,
+    quad::{DualDEdgeEntity, DualDirectedEdge},
+    Mesh, MeshCursor, MeshExtension, PrimalMeshCursor,
 };
 
-pub struct DualMeshCursor<'a, V, F, Cache: Default> {
-    mesh: &'a Mesh<V, F, Cache>,
+pub struct DualMeshCursor<'a, V, F, E, Cache: MeshExtension<V, F, E>> {
+    mesh: &'a Mesh<V, F, E, Cache>,
     entity: DualDEdgeEntity,
 }
 
-impl<'a, V, F, Cache: Default> DualMeshCursor<'a, V, F, Cache> {
-    /// Drop reference to mesh.
-    pub fn id(self) -> DualDEdgeEntity {
-        self.entity
+impl<'a, V, F, E, Cache: MeshExtension<V, F, E>> Clone for DualMeshCursor<'a, V, F, E, Cache> {
+    fn clone(&self) -> Self {
+        *self
     }
 }
+impl<'a, V, F, E, Cache: MeshExtension<V, F, E>> Copy for DualMeshCursor<'a, V, F, E, Cache> {}
 
-impl<'a, V, F, Cache: Default> DualMeshCursor<'a, V, F, Cache> {
+impl<'a, V, F, E, Cache: MeshExtension<V, F, E>> DualMeshCursor<'a, V, F, E, Cache> {
     pub fn new(
-        mesh: &'a Mesh<V, F, Cache>,
+        mesh: &'a Mesh<V, F, E, Cache>,
         entity: DualDEdgeEntity,
-    ) -> DualMeshCursor<'a, V, F, Cache> {
+    ) -> DualMeshCursor<'a, V, F, E, Cache> {
         DualMeshCursor { mesh, entity }
     }
-    pub fn org(&self) -> &RefCell<F> {
+    pub fn org(&self) -> &'a F {
+        self.mesh.get_face(self.mesh.get_dual(self.entity).org)
+    }
+    pub fn dest(&self) -> &'a F {
         self.mesh
-            .get_face(self.mesh.get_dual(self.entity).borrow().org)
+            .get_face(self.mesh.get_dual(self.entity.sym()).org)
     }
-    pub fn dest(&self) -> &RefCell<F> {
+    /// The primal vertex to the left of this dual dedge.
+    pub fn left(&self) -> &'a V {
         self.mesh
-            .get_face(self.mesh.get_dual(self.entity.sym()).borrow().org)
+            .get_vertex(self.mesh.get_primal(self.entity.rot_inv()).org)
     }
-    pub fn get(&self) -> &RefCell<DualDirectedEdge> {
+    /// The primal vertex to the right of this dual dedge.
+    pub fn right(&self) -> &'a V {
+        self.mesh
+            .get_vertex(self.mesh.get_primal(self.entity.rot()).org)
+    }
+    pub fn get(&self) -> &'a DualDirectedEdge {
         self.mesh.get_dual(self.entity)
     }
 
-    fn extend(&self, entity: DualDEdgeEntity) -> DualMeshCursor<'a, V, F, Cache> {
+    fn extend(&self, entity: DualDEdgeEntity) -> DualMeshCursor<'a, V, F, E, Cache> {
         DualMeshCursor {
             mesh: self.mesh,
             entity,
         }
     }
-    fn extend_other(&self, entity: PrimalDEdgeEntity) -> PrimalMeshCursor<'a, V, F, Cache> {
-        PrimalMeshCursor::new(self.mesh, entity)
-    }
+}
 
-    pub fn onext(&self) -> DualMeshCursor<'a, V, F, Cache> {
-        self.extend(self.mesh.get_dual(self.entity).borrow().onext)
-    }
-    pub fn oprev(&self) -> DualMeshCursor<'a, V, F, Cache> {
-        self.extend(self.mesh.get_primal(self.entity.rot()).borrow().onext.rot())
+impl<'a, V, F, E, Cache: MeshExtension<V, F, E>> MeshCursor for DualMeshCursor<'a, V, F, E, Cache> {
+    type Entity = DualDEdgeEntity;
+    type Other = PrimalMeshCursor<'a, V, F, E, Cache>;
+
+    fn id(&self) -> DualDEdgeEntity {
+        self.entity
     }
-    pub fn lnext(&self) -> DualMeshCursor<'a, V, F, Cache> {
-        self.extend(
-            self.mesh
-                .get_primal(self.entity.rot_inv())
-                .borrow()
-                .onext
-                .rot(),
-        )
+    fn onext(&self) -> Self {
+        self.extend(self.mesh.get_dual(self.entity).onext)
     }
-    pub fn sym(&self) -> DualMeshCursor<V, F, Cache> {
+    fn sym(&self) -> Self {
         self.extend(self.entity.sym())
     }
+    fn rot(&self) -> PrimalMeshCursor<'a, V, F, E, Cache> {
+        PrimalMeshCursor::new(self.mesh, self.entity.rot())
+    }
+    fn rot_inv(&self) -> PrimalMeshCursor<'a, V, F, E, Cache> {
+        PrimalMeshCursor::new(self.mesh, self.entity.rot_inv())
+    }
 }
@@ -0,0 +1,120 @@
+use std::{error::Error, fmt};
+
+use super::{
+    quad::{DualDEdgeEntity, FaceEntity, PrimalDEdgeEntity, VertexEntity},
+    SlotStatus,
+};
+
+/// Any entity handle, for error reporting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityRef {
+    PrimalDEdge(PrimalDEdgeEntity),
+    DualDEdge(DualDEdgeEntity),
+    Vertex(VertexEntity),
+    Face(FaceEntity),
+}
+
+impl From<PrimalDEdgeEntity> for EntityRef {
+    fn from(e: PrimalDEdgeEntity) -> Self {
+        EntityRef::PrimalDEdge(e)
+    }
+}
+impl From<DualDEdgeEntity> for EntityRef {
+    fn from(e: DualDEdgeEntity) -> Self {
+        EntityRef::DualDEdge(e)
+    }
+}
+impl From<VertexEntity> for EntityRef {
+    fn from(e: VertexEntity) -> Self {
+        EntityRef::Vertex(e)
+    }
+}
+impl From<FaceEntity> for EntityRef {
+    fn from(e: FaceEntity) -> Self {
+        EntityRef::Face(e)
+    }
+}
+
+impl fmt::Display for EntityRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntityRef::PrimalDEdge(e) => write!(f, "{:?}", e),
+            EntityRef::DualDEdge(e) => write!(f, "{:?}", e),
+            EntityRef::Vertex(e) => write!(f, "{:?}", e),
+            EntityRef::Face(e) => write!(f, "{:?}", e),
+        }
+    }
+}
+
+/// Recoverable failures of the fallible `try_*` mesh operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MeshError {
+    /// The handle points at a slot that was never allocated, or was reserved but never filled.
+    DanglingEntity(EntityRef),
+    /// The handle's element has been deleted. Its slot may since have been reused.
+    DeletedEntity(EntityRef),
+    /// The operation needs at least one edge.
+    EmptyMesh,
+}
+
+impl MeshError {
+    /// The error for a handle whose slot is not live.
+    pub(crate) fn missing(status: SlotStatus, entity: impl Into<EntityRef>) -> Self {
+        match status {
+            SlotStatus::Stale => MeshError::DeletedEntity(entity.into()),
+            _ => MeshError::DanglingEntity(entity.into()),
+        }
+    }
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeshError::DanglingEntity(e) => write!(f, "{} is dangling", e),
+            MeshError::DeletedEntity(e) => write!(f, "{} is stale, it has been deleted", e),
+            MeshError::EmptyMesh => write!(f, "mesh has no edges"),
+        }
+    }
+}
+
+impl Error for MeshError {}
+
+/// Polygons that [`Mesh::from_polygons`](super::Mesh::from_polygons) can't build a manifold
+/// mesh from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolygonError {
+    /// Polygon `polygon` refers to vertex index `vertex`, past the end of the vertex list.
+    VertexOutOfRange { polygon: usize, vertex: usize },
+    /// The polygon has fewer than three corners or visits a vertex twice.
+    DegeneratePolygon(usize),
+    /// The directed edge `org -> dest` appears in two polygons. Either more than two polygons
+    /// share the edge or two neighbours disagree on orientation.
+    NonManifoldEdge { org: usize, dest: usize },
+    /// The polygons around the vertex do not form a single fan.
+    NonManifoldVertex(usize),
+}
+
+impl fmt::Display for PolygonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolygonError::VertexOutOfRange { polygon, vertex } => {
+                write!(f, "polygon {} refers to missing vertex {}", polygon, vertex)
+            }
+            PolygonError::DegeneratePolygon(polygon) => write!(
+                f,
+                "polygon {} has fewer than three corners or repeats one",
+                polygon
+            ),
+            PolygonError::NonManifoldEdge { org, dest } => write!(
+                f,
+                "edge {} -> {} is used by two polygons, the input is non-manifold or inconsistently oriented",
+                org, dest
+            ),
+            PolygonError::NonManifoldVertex(v) => {
+                write!(f, "polygons around vertex {} do not form a single fan", v)
+            }
+        }
+    }
+}
+
+impl Error for PolygonError {}
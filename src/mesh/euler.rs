@@ -0,0 +1,203 @@
+use std::collections::HashSet;
+
+use super::{
+    quad::{FaceEntity, PrimalDEdgeEntity, VertexEntity},
+    Mesh, MeshExtension,
+};
+
+/// Euler operators, built on the splice primitives. Each one leaves every face and vertex
+/// label consistent with the rings it ends up in.
+impl<V, F, E, Cache: MeshExtension<V, F, E>> Mesh<V, F, E, Cache> {
+    /// Split `e` at the isolated vertex `v`. `e` is shortened to end at `v` and the returned
+    /// edge continues from `v` to the old destination, with the same faces on either side and
+    /// a copy of the payload of `e`.
+    pub fn split_edge(&mut self, e: PrimalDEdgeEntity, v: VertexEntity) -> PrimalDEdgeEntity
+    where
+        E: Clone,
+    {
+        let dest = self.get_primal(e.sym()).org;
+        let left = self.get_dual(e.rot_inv()).org;
+        let right = self.get_dual(e.rot()).org;
+        let e_sym_oprev = self.get_dual(e.rot_inv()).onext.rot();
+
+        let data = self.get_edge(e).clone();
+        let n = self.make_edge_with(v, dest, left, right, data);
+
+        // n.sym takes the place of e.sym around the destination
+        if e_sym_oprev != e.sym() {
+            self.splice_primal(e.sym(), e_sym_oprev);
+            self.splice_primal(n.sym(), e_sym_oprev);
+        }
+        self.get_primal_mut(e.sym()).org = v;
+        self.splice_primal(e.sym(), n);
+
+        n
+    }
+
+    /// Contract `e` into its origin. The destination vertex is deleted and its other edges
+    /// are moved to the origin.
+    ///
+    /// A face on either side of `e` that is left with only two edges (a collapsed triangle)
+    /// is removed too, by deleting one of its edges and merging it into the face beyond.
+    pub fn collapse_edge(&mut self, e: PrimalDEdgeEntity) {
+        let org = self.get_primal(e).org;
+        let dest = self.get_primal(e.sym()).org;
+        assert_ne!(org, dest, "{:?} is a loop", e);
+        let e_oprev = self.get_dual(e.rot()).onext.rot();
+        let e_sym_oprev = self.get_dual(e.rot_inv()).onext.rot();
+        // lnext of `e` and of `e.sym`, the next sides of the faces either side of it
+        let sides = [e_sym_oprev, e_oprev];
+
+        let moved = self
+            .get_primal_onext_ring(e.sym())
+            .skip(1)
+            .collect::<Vec<_>>();
+        for m in moved {
+            self.get_primal_mut(m).org = org;
+        }
+
+        // the destination's ring goes where `e` was in the origin's ring
+        self.delete_primal(e);
+        if e_oprev != e && e_sym_oprev != e.sym() {
+            self.splice_primal(e_oprev, e_sym_oprev);
+        }
+        self.delete_verted(dest);
+
+        for side in sides {
+            if side.edge() == e.edge() || self.try_get_primal(side).is_err() {
+                continue;
+            }
+            let ring = self.get_lnext_ring(side).collect::<Vec<_>>();
+            let digon = self.get_dual(side.rot_inv()).org;
+            let beyond = self.get_dual(side.rot()).org;
+            if ring.len() == 2 && digon != beyond {
+                self.delete_primal(side);
+                self.set_left_face(ring[1], beyond);
+                self.delete_face(digon);
+            }
+        }
+    }
+
+    /// Split the left face of `from` and `to` with a new edge from the end of `from` to the
+    /// start of `to`, see [`Mesh::connect_primal`].
+    ///
+    /// The half with `from` and `to` on its boundary keeps the old face, on the left of the
+    /// returned edge. The other half gets a new face with payload `face`.
+    pub fn split_face(
+        &mut self,
+        from: PrimalDEdgeEntity,
+        to: PrimalDEdgeEntity,
+        face: F,
+    ) -> (PrimalDEdgeEntity, FaceEntity)
+    where
+        E: Default,
+    {
+        let old = self.get_dual(from.rot_inv()).org;
+        assert_eq!(
+            old,
+            self.get_dual(to.rot_inv()).org,
+            "{:?} and {:?} don't share a left face",
+            from,
+            to
+        );
+
+        let e = self.connect_primal(from, to);
+        let new = self.insert_face(face);
+        self.set_left_face(e, old);
+        self.set_left_face(e.sym(), new);
+
+        (e, new)
+    }
+
+    /// Delete `e`, merging the face on its right into the face on its left. Returns the
+    /// surviving left face. The right face is deleted.
+    pub fn merge_faces(&mut self, e: PrimalDEdgeEntity) -> FaceEntity {
+        let left = self.get_dual(e.rot_inv()).org;
+        let right = self.get_dual(e.rot()).org;
+        assert_ne!(left, right, "{:?} has the same face on both sides", e);
+        let e_oprev = self.get_dual(e.rot()).onext.rot();
+
+        self.delete_primal(e);
+        self.set_left_face(e_oprev, left);
+        self.delete_face(right);
+
+        left
+    }
+
+    /// Delete the origin of `e` along with every edge leaving it, the inverse of
+    /// [`Mesh::face_to_vertex`]. The faces around the vertex merge into a face for each lnext
+    /// ring left behind. The first of them, going round from `e`, keeps the left face of `e`,
+    /// which is returned.
+    ///
+    /// Removing a cut vertex leaves several rings. Each one takes over a face from around the
+    /// vertex, or gets a new face with a copy of the payload of the face it was part of once
+    /// those run out. Faces nobody takes over are deleted, and so are neighbours left without
+    /// any edges.
+    pub fn remove_vertex(&mut self, e: PrimalDEdgeEntity) -> FaceEntity
+    where
+        F: Clone,
+    {
+        let vertex = self.get_primal(e).org;
+        let left = self.get_dual(e.rot_inv()).org;
+        let star = self.get_primal_onext_ring(e).collect::<Vec<_>>();
+        let is_spoke = |l: PrimalDEdgeEntity| star.iter().any(|s| s.edge() == l.edge());
+        // the face of each wedge, and its far edge unless that is a spoke of the star itself
+        let wedges = star
+            .iter()
+            .map(|s| {
+                let face = self.get_dual(s.rot_inv()).org;
+                let far = self.get_dual(s.rot_inv()).onext.rot();
+                (face, Some(far).filter(|&l| !is_spoke(l)))
+            })
+            .collect::<Vec<_>>();
+        let isolated = star
+            .iter()
+            .map(|s| s.sym())
+            .filter(|&s| self.get_primal(s).org != vertex)
+            .filter(|&s| self.get_primal_onext_ring(s).all(is_spoke))
+            .map(|s| self.get_primal(s).org)
+            .collect::<Vec<_>>();
+
+        for &s in &star {
+            self.delete_primal(s);
+        }
+        let mut relabelled = HashSet::new();
+        let mut used = vec![left];
+        for &(face, far) in &wedges {
+            let far = match far {
+                Some(far) if !relabelled.contains(&far) => far,
+                _ => continue,
+            };
+            // the first ring keeps `left`
+            let face = if relabelled.is_empty() {
+                left
+            } else if !used.contains(&face) {
+                face
+            } else {
+                let data = self.get_face(face).clone();
+                self.insert_face(data)
+            };
+            relabelled.extend(self.get_lnext_ring(far));
+            self.set_left_face(far, face);
+            used.push(face);
+        }
+        for (face, _) in wedges {
+            if !used.contains(&face) && self.try_get_face(face).is_ok() {
+                self.delete_face(face);
+            }
+        }
+        for neighbour in isolated {
+            self.delete_verted(neighbour);
+        }
+        self.delete_verted(vertex);
+
+        left
+    }
+
+    /// Label the whole lnext ring of `e` with `face`.
+    fn set_left_face(&mut self, e: PrimalDEdgeEntity, face: FaceEntity) {
+        for d in self.get_dual_onext_ring(e.rot_inv()).collect::<Vec<_>>() {
+            self.set_dual_org(d, face);
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use super::{
+    quad::{DualDEdgeEntity, PrimalDEdgeEntity},
+    Mesh,
+};
+
+/// State kept alongside a mesh in [`Mesh::cache`](super::Mesh::cache), such as point location
+/// hints, spatial indexes or statistics, which is told about every topology edit so it can
+/// keep itself up to date.
+///
+/// Callbacks run after the edit, whether or not the change log is on. Compound edits report
+/// the primitives they are made of, as in [`MeshChange`](super::MeshChange): deleting an edge
+/// first splices it out of both its rings.
+///
+/// `V`, `F` and `E` are the payloads of the meshes it can be kept with, so an extension can
+/// also keep payloads derived from the geometry up to date, see [`MeshExtension::settle`].
+pub trait MeshExtension<V, F, E>: Default {
+    /// `e` was created, alone in its onext ring at both ends.
+    fn edge_created(&mut self, _e: PrimalDEdgeEntity) {}
+
+    /// `e` was deleted, so the handle is now stale.
+    fn edge_deleted(&mut self, _e: PrimalDEdgeEntity) {}
+
+    /// `a` and `b` were spliced, by [`Mesh::splice_primal`](super::Mesh::splice_primal) or the
+    /// matching [`Mesh::splice_dual`](super::Mesh::splice_dual).
+    fn spliced(&mut self, _a: PrimalDEdgeEntity, _b: PrimalDEdgeEntity) {}
+
+    /// `e` was rotated by [`Mesh::swap_primal`](super::Mesh::swap_primal) to join the other
+    /// two corners of its faces.
+    fn swapped(&mut self, _e: PrimalDEdgeEntity) {}
+
+    /// [`Mesh::set_dual_org`](super::Mesh::set_dual_org) gave `e` a different face, which is
+    /// already written.
+    fn face_relabelled(&mut self, _e: DualDEdgeEntity) {}
+
+    /// Anything may have changed, after [`Mesh::revert`](super::Mesh::revert) or
+    /// [`Mesh::compact`](super::Mesh::compact), and any entity held may now mean something
+    /// else. Starts over from the default.
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Called with the whole mesh as [`Mesh::swap_primal`], [`Mesh::delete_primal`],
+    /// [`Mesh::revert`] and [`Mesh::compact`] return, after the callbacks for the edits they
+    /// made. Edits made of other primitives, the Euler operators included, don't call it.
+    fn settle(_mesh: &mut Mesh<V, F, E, Self>) {}
+}
+
+/// No extension.
+impl<V, F, E> MeshExtension<V, F, E> for () {}
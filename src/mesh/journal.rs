@@ -0,0 +1,135 @@
+use super::{quad::QuadEdge, Mesh, MeshChange, MeshExtension, SlotJournal};
+
+/// Changes made to a mesh between [`Mesh::begin`] and [`Mesh::commit`], which
+/// [`Mesh::revert`] undoes exactly: generations, free lists and therefore the entities handed
+/// out by later inserts come back as they were.
+#[derive(Debug)]
+pub struct Transaction<V, F, E> {
+    quad_edges: SlotJournal<QuadEdge>,
+    vertices: SlotJournal<V>,
+    faces: SlotJournal<F>,
+    edge_data: SlotJournal<E>,
+}
+
+impl<V, F, E> Transaction<V, F, E> {
+    pub fn is_empty(&self) -> bool {
+        self.quad_edges.is_empty()
+            && self.vertices.is_empty()
+            && self.faces.is_empty()
+            && self.edge_data.is_empty()
+    }
+}
+
+impl<V, F, E, Cache: MeshExtension<V, F, E>> Mesh<V, F, E, Cache> {
+    /// Start recording a transaction.
+    ///
+    /// Every topology edit is recorded: inserted, reserved and deleted edges, vertices and
+    /// faces, all writes through [`Mesh::get_primal_mut`] and [`Mesh::get_dual_mut`], and
+    /// payload writes through [`Mesh::get_vertex_mut`], [`Mesh::get_face_mut`] and
+    /// [`Mesh::get_edge_mut`].
+    pub fn begin(&mut self) {
+        self.quad_edges.begin();
+        self.vertices.begin();
+        self.faces.begin();
+        self.edge_data.begin();
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.quad_edges.is_recording()
+    }
+
+    /// Stop recording and return the changes made since [`Mesh::begin`].
+    pub fn commit(&mut self) -> Transaction<V, F, E> {
+        Transaction {
+            quad_edges: self.quad_edges.end(),
+            vertices: self.vertices.end(),
+            faces: self.faces.end(),
+            edge_data: self.edge_data.end(),
+        }
+    }
+
+    /// Stop recording and undo the changes made since [`Mesh::begin`].
+    pub fn rollback(&mut self) {
+        let transaction = self.commit();
+        self.revert(transaction);
+    }
+
+    /// Undo `transaction`, which must be the last change made to the mesh. Returns the
+    /// transaction that redoes it. The extension is reset, since it may refer to undone edges,
+    /// and [`MeshChange::Reset`] is logged.
+    pub fn revert(&mut self, transaction: Transaction<V, F, E>) -> Transaction<V, F, E> {
+        let redo = Transaction {
+            quad_edges: self.quad_edges.revert(transaction.quad_edges),
+            vertices: self.vertices.revert(transaction.vertices),
+            faces: self.faces.revert(transaction.faces),
+            edge_data: self.edge_data.revert(transaction.edge_data),
+        };
+        self.notify(MeshChange::Reset);
+        Cache::settle(self);
+        redo
+    }
+}
+
+/// Undo and redo stacks of transactions on one mesh.
+///
+/// Only meaningful as long as every change to the mesh goes through it: a change made
+/// outside a transaction must be followed by [`History::clear`].
+#[derive(Debug)]
+pub struct History<V, F, E> {
+    undo: Vec<Transaction<V, F, E>>,
+    redo: Vec<Transaction<V, F, E>>,
+}
+
+impl<V, F, E> Default for History<V, F, E> {
+    fn default() -> Self {
+        Self {
+            undo: Default::default(),
+            redo: Default::default(),
+        }
+    }
+}
+
+impl<V, F, E> History<V, F, E> {
+    /// Add a committed transaction. Anything that could be redone is dropped.
+    pub fn push(&mut self, transaction: Transaction<V, F, E>) {
+        if !transaction.is_empty() {
+            self.undo.push(transaction);
+            self.redo.clear();
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Revert the last transaction. Returns `false` if there was none.
+    pub fn undo<Cache: MeshExtension<V, F, E>>(&mut self, mesh: &mut Mesh<V, F, E, Cache>) -> bool {
+        match self.undo.pop() {
+            Some(transaction) => {
+                self.redo.push(mesh.revert(transaction));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Reapply the last undone transaction. Returns `false` if there was none.
+    pub fn redo<Cache: MeshExtension<V, F, E>>(&mut self, mesh: &mut Mesh<V, F, E, Cache>) -> bool {
+        match self.redo.pop() {
+            Some(transaction) => {
+                self.undo.push(mesh.revert(transaction));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
@@ -1,106 +1,297 @@
-use std::{cell::RefCell, iter};
-
 use self::quad::{
-    DualDEdgeEntity, DualDirectedEdge, FaceEntity, PrimalDEdgeEntity, PrimalDirectedEdge,
+    DualDEdgeEntity, DualDirectedEdge, FaceEntity, PrimalDEdgeEntity, PrimalDirectedEdge, QuadEdge,
     VertexEntity,
 };
 
+mod changes;
+mod compact;
+mod cursor;
 mod dual;
+mod error;
+mod euler;
+mod extension;
+mod journal;
+mod polygons;
 mod primal;
 pub mod quad;
+mod slots;
+mod traversal;
+mod validate;
+pub use changes::MeshChange;
+pub use compact::MeshRemap;
+pub use cursor::MeshCursor;
 pub use dual::DualMeshCursor;
+pub use error::{EntityRef, MeshError, PolygonError};
+pub use extension::MeshExtension;
+pub use journal::{History, Transaction};
 pub use primal::PrimalMeshCursor;
+pub use slots::{Slot, SlotJournal, SlotRemap, SlotStatus, Slots};
+pub use traversal::PrimalLnextRing;
+pub use validate::Violation;
 
 /// Tools for constructing, navigating and manipulating meshes.
 ///
+/// `V`, `F` and `E` are the payloads of vertices, faces and undirected edges. An edge's
+/// payload belongs to its quad-edge, so it stays with the edge through splices and swaps and
+/// goes when the edge is deleted.
+///
+/// Deleted elements leave their slot on a free list to be reused by later inserts. Every
+/// entity carries the generation of its slot, so a handle that outlives its element is
+/// reported as stale instead of silently aliasing the replacement.
+///
+/// Elements are stored by value: topology edits take `&mut self` and reads take `&self`, so a
+/// mesh is `Send + Sync` whenever its payloads are.
+///
+/// With the `serde` feature the storage round-trips exactly, freed slots and generations
+/// included, so stored entity ids stay meaningful.
+///
+/// Front ends can follow edits through the change log, see [`Mesh::start_change_log`].
 #[derive(Debug)]
-pub struct Mesh<V, F, Cache: Default> {
-    pub primal_dedges: Vec<Option<RefCell<PrimalDirectedEdge>>>,
-    pub dual_dedges: Vec<Option<RefCell<DualDirectedEdge>>>,
-    pub vertices: Vec<Option<RefCell<V>>>,
-    pub faces: Vec<Option<RefCell<F>>>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mesh<V, F, E, Cache: MeshExtension<V, F, E>> {
+    pub quad_edges: Slots<QuadEdge>,
+    pub vertices: Slots<V>,
+    pub faces: Slots<F>,
+    /// Edge payloads, each in the same slot as its quad-edge.
+    pub edge_data: Slots<E>,
+    /// Derived from the rest of the mesh and kept up to date through [`MeshExtension`], so it
+    /// is not serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub cache: Cache,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    changes: Option<Vec<MeshChange>>,
 }
 
-impl<V, F, Cache: Default> Default for Mesh<V, F, Cache> {
+impl<V, F, E, Cache: MeshExtension<V, F, E>> Default for Mesh<V, F, E, Cache> {
     fn default() -> Self {
         Self {
-            primal_dedges: Default::default(),
-            dual_dedges: Default::default(),
+            quad_edges: Default::default(),
             vertices: Default::default(),
             faces: Default::default(),
+            edge_data: Default::default(),
             cache: Default::default(),
+            changes: None,
         }
     }
 }
 
-impl<'a, V, F, Cache: Default> Mesh<V, F, Cache> {
+impl<'a, V, F, E, Cache: MeshExtension<V, F, E>> Mesh<V, F, E, Cache> {
     pub fn new() -> Self {
         Mesh::default()
     }
 
     pub fn reserve_vertex(&mut self) -> VertexEntity {
-        let e = VertexEntity(self.vertices.len());
-        self.vertices.push(None);
-        e
+        let (index, generation) = self.vertices.reserve();
+        VertexEntity(index, generation)
     }
 
     pub fn reserve_face(&mut self) -> FaceEntity {
-        let e = FaceEntity(self.faces.len());
-        self.faces.push(None);
-        e
+        let (index, generation) = self.faces.reserve();
+        FaceEntity(index, generation)
     }
 
     pub fn insert_reserved_vertex<U: Into<V>>(&mut self, entity: VertexEntity, v: U) {
-        self.vertices
-            .get_mut(entity.0)
-            .unwrap()
-            .replace(RefCell::new(v.into()));
+        let status = self.vertices.status(entity.0, entity.1);
+        if !self.vertices.fill(entity.0, entity.1, v.into()) {
+            panic!("{:?} is {}", entity, status);
+        }
+        self.notify(MeshChange::VertexInserted(entity));
     }
 
     pub fn insert_reserved_face(&mut self, entity: FaceEntity, f: F) {
+        let status = self.faces.status(entity.0, entity.1);
+        if !self.faces.fill(entity.0, entity.1, f) {
+            panic!("{:?} is {}", entity, status);
+        }
+        self.notify(MeshChange::FaceInserted(entity));
+    }
+
+    pub fn get_primal(&self, entity: PrimalDEdgeEntity) -> &PrimalDirectedEdge {
+        self.try_get_primal(entity)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+    pub fn get_dual(&self, entity: DualDEdgeEntity) -> &DualDirectedEdge {
+        self.try_get_dual(entity)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+    pub fn get_vertex(&self, entity: VertexEntity) -> &V {
+        self.try_get_vertex(entity)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+    pub fn get_face(&self, entity: FaceEntity) -> &F {
+        self.try_get_face(entity)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// The payload of the edge, shared by both directions.
+    pub fn get_edge(&self, entity: PrimalDEdgeEntity) -> &E {
+        self.try_get_edge(entity)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn get_primal_mut(&mut self, entity: PrimalDEdgeEntity) -> &mut PrimalDirectedEdge {
+        self.try_get_primal_mut(entity)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+    pub fn get_dual_mut(&mut self, entity: DualDEdgeEntity) -> &mut DualDirectedEdge {
+        self.try_get_dual_mut(entity)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+    pub fn get_vertex_mut(&mut self, entity: VertexEntity) -> &mut V
+    where
+        V: Clone,
+    {
+        self.try_get_vertex_mut(entity)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+    pub fn get_face_mut(&mut self, entity: FaceEntity) -> &mut F
+    where
+        F: Clone,
+    {
+        self.try_get_face_mut(entity)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+    pub fn get_edge_mut(&mut self, entity: PrimalDEdgeEntity) -> &mut E
+    where
+        E: Clone,
+    {
+        self.try_get_edge_mut(entity)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_get_primal(
+        &self,
+        entity: PrimalDEdgeEntity,
+    ) -> Result<&PrimalDirectedEdge, MeshError> {
+        self.quad_edges
+            .get(entity.edge(), entity.1)
+            .map(|quad_edge| &quad_edge.primal[entity.0 & 1])
+            .ok_or_else(|| {
+                MeshError::missing(self.quad_edges.status(entity.edge(), entity.1), entity)
+            })
+    }
+    pub fn try_get_dual(&self, entity: DualDEdgeEntity) -> Result<&DualDirectedEdge, MeshError> {
+        self.quad_edges
+            .get(entity.edge(), entity.1)
+            .map(|quad_edge| &quad_edge.dual[entity.0 & 1])
+            .ok_or_else(|| {
+                MeshError::missing(self.quad_edges.status(entity.edge(), entity.1), entity)
+            })
+    }
+    pub fn try_get_vertex(&self, entity: VertexEntity) -> Result<&V, MeshError> {
+        self.vertices
+            .get(entity.0, entity.1)
+            .ok_or_else(|| MeshError::missing(self.vertices.status(entity.0, entity.1), entity))
+    }
+    pub fn try_get_face(&self, entity: FaceEntity) -> Result<&F, MeshError> {
         self.faces
-            .get_mut(entity.0)
-            .unwrap()
-            .replace(RefCell::new(f));
+            .get(entity.0, entity.1)
+            .ok_or_else(|| MeshError::missing(self.faces.status(entity.0, entity.1), entity))
+    }
+    pub fn try_get_edge(&self, entity: PrimalDEdgeEntity) -> Result<&E, MeshError> {
+        self.edge_data.get(entity.edge(), entity.1).ok_or_else(|| {
+            MeshError::missing(self.quad_edges.status(entity.edge(), entity.1), entity)
+        })
     }
 
-    pub fn get_primal(&self, entity: PrimalDEdgeEntity) -> &RefCell<PrimalDirectedEdge> {
-        self.primal_dedges.get(entity.0).unwrap().as_ref().unwrap()
+    pub fn try_get_primal_mut(
+        &mut self,
+        entity: PrimalDEdgeEntity,
+    ) -> Result<&mut PrimalDirectedEdge, MeshError> {
+        let status = self.quad_edges.status(entity.edge(), entity.1);
+        if status == SlotStatus::Live {
+            self.notify(MeshChange::PrimalChanged(entity));
+        }
+        self.quad_edges
+            .edit(entity.edge(), entity.1)
+            .map(|quad_edge| &mut quad_edge.primal[entity.0 & 1])
+            .ok_or_else(|| MeshError::missing(status, entity))
+    }
+    pub fn try_get_dual_mut(
+        &mut self,
+        entity: DualDEdgeEntity,
+    ) -> Result<&mut DualDirectedEdge, MeshError> {
+        let status = self.quad_edges.status(entity.edge(), entity.1);
+        if status == SlotStatus::Live {
+            self.notify(MeshChange::DualChanged(entity));
+        }
+        self.quad_edges
+            .edit(entity.edge(), entity.1)
+            .map(|quad_edge| &mut quad_edge.dual[entity.0 & 1])
+            .ok_or_else(|| MeshError::missing(status, entity))
+    }
+    pub fn try_get_vertex_mut(&mut self, entity: VertexEntity) -> Result<&mut V, MeshError>
+    where
+        V: Clone,
+    {
+        let status = self.vertices.status(entity.0, entity.1);
+        if status == SlotStatus::Live {
+            self.notify(MeshChange::VertexMoved(entity));
+        }
+        self.vertices
+            .edit(entity.0, entity.1)
+            .ok_or_else(|| MeshError::missing(status, entity))
     }
-    pub fn get_dual(&self, entity: DualDEdgeEntity) -> &RefCell<DualDirectedEdge> {
-        self.dual_dedges.get(entity.0).unwrap().as_ref().unwrap()
+    pub fn try_get_face_mut(&mut self, entity: FaceEntity) -> Result<&mut F, MeshError>
+    where
+        F: Clone,
+    {
+        let status = self.faces.status(entity.0, entity.1);
+        if status == SlotStatus::Live {
+            self.notify(MeshChange::FaceChanged(entity));
+        }
+        self.faces
+            .edit(entity.0, entity.1)
+            .ok_or_else(|| MeshError::missing(status, entity))
     }
-    pub fn get_vertex(&self, entity: VertexEntity) -> &RefCell<V> {
-        self.vertices.get(entity.0).unwrap().as_ref().unwrap()
+    pub fn try_get_edge_mut(&mut self, entity: PrimalDEdgeEntity) -> Result<&mut E, MeshError>
+    where
+        E: Clone,
+    {
+        let status = self.quad_edges.status(entity.edge(), entity.1);
+        if status == SlotStatus::Live {
+            self.notify(MeshChange::EdgeChanged(entity));
+        }
+        self.edge_data
+            .edit(entity.edge(), entity.1)
+            .ok_or_else(|| MeshError::missing(status, entity))
     }
-    pub fn get_face(&self, entity: FaceEntity) -> &RefCell<F> {
-        self.faces.get(entity.0).unwrap().as_ref().unwrap()
+
+    /// Every live primal dedge, both directions of each edge.
+    pub fn primal_dedges(&self) -> impl Iterator<Item = PrimalDEdgeEntity> + '_ {
+        self.quad_edges.iter().flat_map(|(edge, generation, _)| {
+            let e = PrimalDEdgeEntity::from_edge(edge, generation);
+            [e, e.sym()]
+        })
     }
 
     pub fn insert_vertex<U: Into<V>>(&mut self, v: U) -> VertexEntity {
-        let e = VertexEntity(self.vertices.len());
-        self.vertices.push(Some(RefCell::new(v.into())));
-        e
+        let (index, generation) = self.vertices.insert(v.into());
+        self.notify(MeshChange::VertexInserted(VertexEntity(index, generation)));
+        VertexEntity(index, generation)
     }
 
     pub fn insert_face(&mut self, f: F) -> FaceEntity {
-        let e = FaceEntity(self.faces.len());
-        self.faces.push(Some(RefCell::new(f)));
-        e
+        let (index, generation) = self.faces.insert(f);
+        self.notify(MeshChange::FaceInserted(FaceEntity(index, generation)));
+        FaceEntity(index, generation)
     }
 
     pub fn delete_face(&mut self, entity: FaceEntity) {
-        self.faces.get_mut(entity.0).unwrap().take();
+        if self.faces.remove(entity.0, entity.1) {
+            self.notify(MeshChange::FaceDeleted(entity));
+        }
     }
     pub fn delete_verted(&mut self, entity: VertexEntity) {
-        self.vertices.get_mut(entity.0).unwrap().take();
+        if self.vertices.remove(entity.0, entity.1) {
+            self.notify(MeshChange::VertexDeleted(entity));
+        }
     }
 
     pub fn get_primal_onext_ring(
         &'a self,
         entity: PrimalDEdgeEntity,
-    ) -> PrimalOnextRing<'a, V, F, Cache> {
+    ) -> PrimalOnextRing<'a, V, F, E, Cache> {
         PrimalOnextRing {
             first: entity,
             current: Some(entity),
@@ -111,7 +302,7 @@ impl<'a, V, F, Cache: Default> Mesh<V, F, Cache> {
     pub fn get_dual_onext_ring(
         &'a self,
         entity: DualDEdgeEntity,
-    ) -> DualOnextRing<'a, V, F, Cache> {
+    ) -> DualOnextRing<'a, V, F, E, Cache> {
         DualOnextRing {
             first: entity,
             current: Some(entity),
@@ -125,75 +316,158 @@ impl<'a, V, F, Cache: Default> Mesh<V, F, Cache> {
         dest: VertexEntity,
         left: FaceEntity,
         right: FaceEntity,
+    ) -> PrimalDEdgeEntity
+    where
+        E: Default,
+    {
+        self.make_edge_with(org, dest, left, right, E::default())
+    }
+
+    /// [`Mesh::make_edge`] with the payload `data`.
+    pub fn make_edge_with(
+        &mut self,
+        org: VertexEntity,
+        dest: VertexEntity,
+        left: FaceEntity,
+        right: FaceEntity,
+        data: E,
     ) -> PrimalDEdgeEntity {
-        let entity = PrimalDEdgeEntity(self.primal_dedges.len());
-
-        self.primal_dedges
-            .push(Some(RefCell::new(PrimalDirectedEdge {
-                //entity
-                org,
-                onext: entity,
-            })));
-        self.primal_dedges
-            .push(Some(RefCell::new(PrimalDirectedEdge {
-                //entity.rot.rot
-                org: dest,
-                onext: entity.sym(),
-            })));
-        self.dual_dedges.push(Some(RefCell::new(DualDirectedEdge {
-            //entity.rot
-            org: right,
-            onext: entity.rot_inv(),
-        })));
-        self.dual_dedges.push(Some(RefCell::new(DualDirectedEdge {
-            //entity.rot.rot.rot
-            org: left,
-            onext: entity.rot(),
-        })));
-
-        entity
-    }
-
-    pub fn splice_primal(&self, a: PrimalDEdgeEntity, b: PrimalDEdgeEntity) {
-        let alpha = self.get_primal(a).borrow().onext.rot();
-        let beta = self.get_primal(b).borrow().onext.rot();
-
-        // relabel entities a,b,alpha,beta as the actual directed edges
-        let mut a = self.get_primal(a).borrow_mut();
-        let mut b = self.get_primal(b).borrow_mut();
-        let mut alpha = self.get_dual(alpha).borrow_mut();
-        let mut beta = self.get_dual(beta).borrow_mut();
-
-        // swap onext values
-        let temp = a.onext;
-        a.onext = b.onext;
-        b.onext = temp;
-
-        let temp = alpha.onext;
-        alpha.onext = beta.onext;
-        beta.onext = temp;
-    }
-
-    // fn splice_dual(&self, )
+        let (edge, generation) = self.quad_edges.insert_with(|edge, generation| {
+            let entity = PrimalDEdgeEntity::from_edge(edge, generation);
+            QuadEdge {
+                primal: [
+                    //entity
+                    PrimalDirectedEdge { org, onext: entity },
+                    //entity.rot.rot
+                    PrimalDirectedEdge {
+                        org: dest,
+                        onext: entity.sym(),
+                    },
+                ],
+                dual: [
+                    //entity.rot
+                    DualDirectedEdge {
+                        org: right,
+                        onext: entity.rot_inv(),
+                    },
+                    //entity.rot.rot.rot
+                    DualDirectedEdge {
+                        org: left,
+                        onext: entity.rot(),
+                    },
+                ],
+            }
+        });
+        // both storages are only ever changed together, so they hand out the same slots
+        let data_slot = self.edge_data.insert(data);
+        debug_assert_eq!(data_slot, (edge, generation));
+        let e = PrimalDEdgeEntity::from_edge(edge, generation);
+        self.notify(MeshChange::EdgeCreated(e));
+        e
+    }
+
+    pub fn splice_primal(&mut self, a: PrimalDEdgeEntity, b: PrimalDEdgeEntity) {
+        self.try_splice_primal(a, b)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Fallible [`Mesh::splice_primal`]. The mesh is left untouched on error.
+    pub fn try_splice_primal(
+        &mut self,
+        a: PrimalDEdgeEntity,
+        b: PrimalDEdgeEntity,
+    ) -> Result<(), MeshError> {
+        let a_onext = self.try_get_primal(a)?.onext;
+        let b_onext = self.try_get_primal(b)?.onext;
+        let alpha = a_onext.rot();
+        let beta = b_onext.rot();
+        let alpha_onext = self.try_get_dual(alpha)?.onext;
+        let beta_onext = self.try_get_dual(beta)?.onext;
+
+        // swap onext values. Splicing an edge with itself is the identity.
+        self.edit_primal(a).onext = b_onext;
+        self.edit_primal(b).onext = a_onext;
+
+        self.edit_dual(alpha).onext = beta_onext;
+        self.edit_dual(beta).onext = alpha_onext;
+
+        self.notify(MeshChange::Spliced(a, b));
+        Ok(())
+    }
+
+    pub fn splice_dual(&mut self, a: DualDEdgeEntity, b: DualDEdgeEntity) {
+        self.try_splice_dual(a, b)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Fallible [`Mesh::splice_dual`]. The mesh is left untouched on error.
+    pub fn try_splice_dual(
+        &mut self,
+        a: DualDEdgeEntity,
+        b: DualDEdgeEntity,
+    ) -> Result<(), MeshError> {
+        // a splice swaps the same two dual and two primal rings whichever side it is applied
+        // from, since `a.onext.rot.onext.rot == a`
+        let alpha = self.try_get_dual(a)?.onext.rot();
+        let beta = self.try_get_dual(b)?.onext.rot();
+        self.try_splice_primal(alpha, beta)
+    }
 
     /// Create new primal edge from the end of `from` to the begining of `to`. New edge is onext to from.sym()
     pub fn connect_primal(
         &mut self,
         from: PrimalDEdgeEntity,
         to: PrimalDEdgeEntity,
+    ) -> PrimalDEdgeEntity
+    where
+        E: Default,
+    {
+        self.try_connect_primal_with(from, to, E::default())
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// [`Mesh::connect_primal`] with the payload `data` for the new edge.
+    pub fn connect_primal_with(
+        &mut self,
+        from: PrimalDEdgeEntity,
+        to: PrimalDEdgeEntity,
+        data: E,
     ) -> PrimalDEdgeEntity {
-        let org = self.get_primal(from.sym()).borrow().org;
-        let dest = self.get_primal(to).borrow().org;
-        let left = self.get_dual(from.rot_inv()).borrow().org;
-        let right = self.get_dual(from.rot()).borrow().org;
+        self.try_connect_primal_with(from, to, data)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
 
-        let e = self.make_edge(org, dest, left, right);
-        let from_lnext = self.get_dual(from.rot_inv()).borrow().onext.rot();
+    /// Fallible [`Mesh::connect_primal`]. The mesh is left untouched on error.
+    pub fn try_connect_primal(
+        &mut self,
+        from: PrimalDEdgeEntity,
+        to: PrimalDEdgeEntity,
+    ) -> Result<PrimalDEdgeEntity, MeshError>
+    where
+        E: Default,
+    {
+        self.try_connect_primal_with(from, to, E::default())
+    }
+
+    /// Fallible [`Mesh::connect_primal_with`].
+    pub fn try_connect_primal_with(
+        &mut self,
+        from: PrimalDEdgeEntity,
+        to: PrimalDEdgeEntity,
+        data: E,
+    ) -> Result<PrimalDEdgeEntity, MeshError> {
+        let org = self.try_get_primal(from.sym())?.org;
+        let dest = self.try_get_primal(to)?.org;
+        let left = self.try_get_dual(from.rot_inv())?.org;
+        let right = self.try_get_dual(from.rot())?.org;
+        let from_lnext = self.try_get_dual(from.rot_inv())?.onext.rot();
 
-        self.splice_primal(e, from_lnext);
-        self.splice_primal(e.sym(), to);
+        let e = self.make_edge_with(org, dest, left, right, data);
 
-        e
+        self.try_splice_primal(e, from_lnext)?;
+        self.try_splice_primal(e.sym(), to)?;
+
+        Ok(e)
     }
 
     /// Create new primal edge that extends from a primal edge `dest` to a dangling vertex
@@ -201,12 +475,25 @@ impl<'a, V, F, Cache: Default> Mesh<V, F, Cache> {
         &mut self,
         old_edge: PrimalDEdgeEntity,
         dest: VertexEntity,
+    ) -> PrimalDEdgeEntity
+    where
+        E: Default,
+    {
+        self.connect_vertex_with(old_edge, dest, E::default())
+    }
+
+    /// [`Mesh::connect_vertex`] with the payload `data` for the new edge.
+    pub fn connect_vertex_with(
+        &mut self,
+        old_edge: PrimalDEdgeEntity,
+        dest: VertexEntity,
+        data: E,
     ) -> PrimalDEdgeEntity {
-        let org = self.get_primal(old_edge.sym()).borrow().org;
-        let left = self.get_dual(old_edge.rot_inv()).borrow().org;
-        let right = self.get_dual(old_edge.rot()).borrow().org;
+        let org = self.get_primal(old_edge.sym()).org;
+        let left = self.get_dual(old_edge.rot_inv()).org;
+        let right = self.get_dual(old_edge.rot()).org;
 
-        let e = self.make_edge(org, dest, left, right);
+        let e = self.make_edge_with(org, dest, left, right, data);
 
         self.splice_primal(e, old_edge.sym());
 
@@ -214,49 +501,97 @@ impl<'a, V, F, Cache: Default> Mesh<V, F, Cache> {
     }
 
     pub fn delete_primal(&mut self, e: PrimalDEdgeEntity) {
-        let e_oprev = self.get_dual(e.rot()).borrow().onext.rot();
-        let e_sym_oprev = self.get_dual(e.rot_inv()).borrow().onext.rot();
-        self.splice_primal(e, e_oprev);
-        self.splice_primal(e.sym(), e_sym_oprev);
+        self.try_delete_primal(e)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
 
-        self.primal_dedges.get_mut(e.0).unwrap().take();
-        self.primal_dedges.get_mut(e.sym().0).unwrap().take();
-        self.dual_dedges.get_mut(e.rot().0).unwrap().take();
-        self.dual_dedges.get_mut(e.rot_inv().0).unwrap().take();
+    /// Fallible [`Mesh::delete_primal`]. The mesh is left untouched on error.
+    pub fn try_delete_primal(&mut self, e: PrimalDEdgeEntity) -> Result<(), MeshError> {
+        let e_oprev = self.try_get_dual(e.rot())?.onext.rot();
+        let e_sym_oprev = self.try_get_dual(e.rot_inv())?.onext.rot();
+        self.try_splice_primal(e, e_oprev)?;
+        self.try_splice_primal(e.sym(), e_sym_oprev)?;
+
+        self.quad_edges.remove(e.edge(), e.1);
+        self.edge_data.remove(e.edge(), e.1);
+        self.notify(MeshChange::EdgeDeleted(e));
+        Cache::settle(self);
+        Ok(())
     }
 
-    pub fn swap_primal(&self, e: PrimalDEdgeEntity) {
-        let a = self.get_dual(e.rot()).borrow().onext.rot();
-        let b = self.get_dual(e.rot_inv()).borrow().onext.rot();
-        let a_lnext = self.get_dual(a.rot_inv()).borrow().onext.rot();
-        let b_lnext = self.get_dual(b.rot_inv()).borrow().onext.rot();
+    pub fn swap_primal(&mut self, e: PrimalDEdgeEntity) {
+        let a = self.get_dual(e.rot()).onext.rot();
+        let b = self.get_dual(e.rot_inv()).onext.rot();
+        let a_lnext = self.get_dual(a.rot_inv()).onext.rot();
+        let b_lnext = self.get_dual(b.rot_inv()).onext.rot();
+        let left = self.get_dual(e.rot_inv()).org;
+        let right = self.get_dual(e.rot()).org;
 
         self.splice_primal(e, a);
         self.splice_primal(e.sym(), b);
         self.splice_primal(e, a_lnext);
         self.splice_primal(e.sym(), b_lnext);
 
-        let org = self.get_primal(a.sym()).borrow().org;
-        let dest = self.get_primal(b.sym()).borrow().org;
+        let org = self.get_primal(a.sym()).org;
+        let dest = self.get_primal(b.sym()).org;
+
+        self.edit_primal(e).org = org;
+        self.edit_primal(e.sym()).org = dest;
+
+        // both new faces straddle the two old ones, so relabel their lnext rings
+        for (d, face) in [(e.rot_inv(), left), (e.rot(), right)] {
+            for ring_dedge in self.get_dual_onext_ring(d).collect::<Vec<_>>() {
+                self.edit_dual(ring_dedge).org = face;
+            }
+        }
+        self.notify(MeshChange::Swapped(e));
+        Cache::settle(self);
+    }
+
+    /// Label the face `entity` leaves with `face`, reporting [`MeshChange::FaceRelabelled`] if
+    /// that changed it.
+    pub fn set_dual_org(&mut self, entity: DualDEdgeEntity, face: FaceEntity) {
+        let dual = self.edit_dual(entity);
+        if dual.org != face {
+            dual.org = face;
+            self.notify(MeshChange::FaceRelabelled(entity));
+        }
+    }
 
-        self.get_primal(e).borrow_mut().org = org;
-        self.get_primal(e.sym()).borrow_mut().org = dest;
+    /// [`Mesh::get_primal_mut`] for edits that log a change of their own.
+    fn edit_primal(&mut self, entity: PrimalDEdgeEntity) -> &mut PrimalDirectedEdge {
+        let status = self.quad_edges.status(entity.edge(), entity.1);
+        self.quad_edges
+            .edit(entity.edge(), entity.1)
+            .map(|quad_edge| &mut quad_edge.primal[entity.0 & 1])
+            .unwrap_or_else(|| panic!("{}", MeshError::missing(status, entity)))
+    }
+    /// [`Mesh::get_dual_mut`] for edits that log a change of their own.
+    fn edit_dual(&mut self, entity: DualDEdgeEntity) -> &mut DualDirectedEdge {
+        let status = self.quad_edges.status(entity.edge(), entity.1);
+        self.quad_edges
+            .edit(entity.edge(), entity.1)
+            .map(|quad_edge| &mut quad_edge.dual[entity.0 & 1])
+            .unwrap_or_else(|| panic!("{}", MeshError::missing(status, entity)))
     }
 
-    pub fn primal(&'a self, e: PrimalDEdgeEntity) -> PrimalMeshCursor<'a, V, F, Cache> {
+    pub fn primal(&'a self, e: PrimalDEdgeEntity) -> PrimalMeshCursor<'a, V, F, E, Cache> {
         PrimalMeshCursor::new(self, e)
     }
 
-    pub fn dual(&'a self, e: DualDEdgeEntity) -> DualMeshCursor<'a, V, F, Cache> {
+    pub fn dual(&'a self, e: DualDEdgeEntity) -> DualMeshCursor<'a, V, F, E, Cache> {
         DualMeshCursor::new(self, e)
     }
 
     pub fn face_to_vertex(
         &mut self,
         from_face: DualDEdgeEntity,
-    ) -> (VertexEntity, Vec<FaceEntity>, Vec<PrimalDEdgeEntity>) {
+    ) -> (VertexEntity, Vec<FaceEntity>, Vec<PrimalDEdgeEntity>)
+    where
+        E: Default,
+    {
         // delete the face object, but don't touch the `FaceEntities` in the `DualDedge::org`s
-        let old_face = self.get_dual(from_face).borrow().org;
+        let old_face = self.get_dual(from_face).org;
         self.delete_face(old_face);
 
         let new_vertex = self.reserve_vertex();
@@ -266,13 +601,13 @@ impl<'a, V, F, Cache: Default> Mesh<V, F, Cache> {
             .get_dual_onext_ring(from_face)
             .map(|dual| dual.rot())
             .collect::<Vec<_>>();
-        let new_faces = iter::repeat(lnext_ring.len())
+        let new_faces = (0..lnext_ring.len())
             .map(|_| self.reserve_face())
             .collect::<Vec<_>>();
         let mut new_edges = Vec::new(); //with_capacity(new_faces.len());
 
         let first_outer_edge = from_face.rot(); //lnext_ring.next().unwrap();
-        let org = self.get_primal(first_outer_edge).borrow().org;
+        let org = self.get_primal(first_outer_edge).org;
 
         let mut newest_dedge_inward = self.make_edge(org, new_vertex, new_faces[0], new_faces[1]);
         new_edges.push(newest_dedge_inward);
@@ -295,17 +630,17 @@ impl<'a, V, F, Cache: Default> Mesh<V, F, Cache> {
     }
 }
 
-pub struct PrimalOnextRing<'a, V, F, Cache: Default> {
+pub struct PrimalOnextRing<'a, V, F, E, Cache: MeshExtension<V, F, E>> {
     first: PrimalDEdgeEntity,
     current: Option<PrimalDEdgeEntity>,
-    mesh: &'a Mesh<V, F, Cache>,
+    mesh: &'a Mesh<V, F, E, Cache>,
 }
 
-impl<'a, V, F, Cache: Default> Iterator for PrimalOnextRing<'a, V, F, Cache> {
+impl<'a, V, F, E, Cache: MeshExtension<V, F, E>> Iterator for PrimalOnextRing<'a, V, F, E, Cache> {
     type Item = PrimalDEdgeEntity;
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(current) = self.current {
-            let next = self.mesh.get_primal(current).borrow().onext;
+            let next = self.mesh.get_primal(current).onext;
             self.current = if next != self.first { Some(next) } else { None };
             Some(current)
         } else {
@@ -315,16 +650,16 @@ impl<'a, V, F, Cache: Default> Iterator for PrimalOnextRing<'a, V, F, Cache> {
 }
 
 //
-pub struct DualOnextRing<'a, V, F, Cache: Default> {
+pub struct DualOnextRing<'a, V, F, E, Cache: MeshExtension<V, F, E>> {
     first: DualDEdgeEntity,
     current: Option<DualDEdgeEntity>,
-    mesh: &'a Mesh<V, F, Cache>,
+    mesh: &'a Mesh<V, F, E, Cache>,
 }
-impl<'a, V, F, Cache: Default> Iterator for DualOnextRing<'a, V, F, Cache> {
+impl<'a, V, F, E, Cache: MeshExtension<V, F, E>> Iterator for DualOnextRing<'a, V, F, E, Cache> {
     type Item = DualDEdgeEntity;
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(current) = self.current {
-            let next = self.mesh.get_dual(current).borrow().onext;
+            let next = self.mesh.get_dual(current).onext;
             self.current = if next != self.first { Some(next) } else { None };
             Some(current)
         } else {
@@ -0,0 +1,154 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{
+    quad::{FaceEntity, PrimalDEdgeEntity},
+    Mesh, MeshExtension, PolygonError,
+};
+
+impl<V, F, E, Cache: MeshExtension<V, F, E>> Mesh<V, F, E, Cache> {
+    /// Build a mesh from a vertex list and polygons given as counter-clockwise loops of
+    /// indices into it.
+    ///
+    /// Vertex `i` becomes `VertexEntity(i, 0)` and polygon `i` gets the face
+    /// `FaceEntity(i, 0)` with payload `face(Some(i))`. Edges used by only one polygon form
+    /// boundary loops, and each loop gets its own outer face with payload `face(None)`.
+    ///
+    /// Fails if a polygon is degenerate or refers to a missing vertex, if an edge is shared
+    /// by more than two polygons or by two polygons of opposite orientation, or if the
+    /// polygons around a vertex do not form a single fan.
+    pub fn from_polygons<P: AsRef<[usize]>>(
+        vertices: impl IntoIterator<Item = V>,
+        polygons: &[P],
+        mut face: impl FnMut(Option<usize>) -> F,
+    ) -> Result<Self, PolygonError>
+    where
+        E: Default,
+    {
+        let mut mesh = Mesh::new();
+        let vertices = vertices
+            .into_iter()
+            .map(|v| mesh.insert_vertex(v))
+            .collect::<Vec<_>>();
+
+        // every directed side of every polygon, keyed by (org, dest)
+        let mut sides = HashMap::new();
+        for (i, polygon) in polygons.iter().enumerate() {
+            let polygon = polygon.as_ref();
+            if let Some(&vertex) = polygon.iter().find(|&&v| v >= vertices.len()) {
+                return Err(PolygonError::VertexOutOfRange { polygon: i, vertex });
+            }
+            if polygon.len() < 3 || polygon.iter().collect::<HashSet<_>>().len() != polygon.len() {
+                return Err(PolygonError::DegeneratePolygon(i));
+            }
+            for (org, dest) in sides_of(polygon) {
+                if sides.insert((org, dest), i).is_some() {
+                    return Err(PolygonError::NonManifoldEdge { org, dest });
+                }
+            }
+        }
+
+        let faces = (0..polygons.len())
+            .map(|i| mesh.insert_face(face(Some(i))))
+            .collect::<Vec<_>>();
+
+        // one quad-edge per undirected edge. Both directions are keyed, `left` is only known
+        // for polygon sides until the boundary loops get their faces.
+        let mut dedges = HashMap::new();
+        let mut left = HashMap::new();
+        let mut boundary = Vec::new();
+        for (i, polygon) in polygons.iter().enumerate() {
+            for (org, dest) in sides_of(polygon.as_ref()) {
+                if !dedges.contains_key(&(org, dest)) {
+                    let e = mesh.make_edge(vertices[org], vertices[dest], faces[i], faces[i]);
+                    dedges.insert((org, dest), e);
+                    dedges.insert((dest, org), e.sym());
+                    if !sides.contains_key(&(dest, org)) {
+                        boundary.push((dest, org));
+                    }
+                }
+                left.insert(dedges[&(org, dest)], faces[i]);
+            }
+        }
+
+        // a vertex on the boundary has exactly one gap in its fan
+        let mut boundary_out = HashMap::new();
+        let mut boundary_in = HashMap::new();
+        for &(org, dest) in &boundary {
+            if boundary_out.insert(org, dest).is_some() {
+                return Err(PolygonError::NonManifoldVertex(org));
+            }
+            if boundary_in.insert(dest, org).is_some() {
+                return Err(PolygonError::NonManifoldVertex(dest));
+            }
+        }
+
+        // onext(e) = lprev(e).sym. Around a polygon lprev is the previous side, around a
+        // boundary loop it is the boundary edge coming into the origin.
+        for polygon in polygons {
+            let polygon = polygon.as_ref();
+            for (k, (org, dest)) in sides_of(polygon).enumerate() {
+                let prev = polygon[(k + polygon.len() - 1) % polygon.len()];
+                mesh.get_primal_mut(dedges[&(org, dest)]).onext = dedges[&(org, prev)];
+            }
+        }
+        for &(org, dest) in &boundary {
+            let prev = boundary_in[&org];
+            mesh.get_primal_mut(dedges[&(org, dest)]).onext = dedges[&(org, prev)];
+        }
+
+        // each boundary loop becomes an outer face
+        let mut outer = HashMap::<PrimalDEdgeEntity, FaceEntity>::new();
+        for &(org, dest) in &boundary {
+            let start = dedges[&(org, dest)];
+            if outer.contains_key(&start) {
+                continue;
+            }
+            let outer_face = mesh.insert_face(face(None));
+            let (mut org, mut dest) = (org, dest);
+            loop {
+                outer.insert(dedges[&(org, dest)], outer_face);
+                (org, dest) = (dest, boundary_out[&dest]);
+                if dedges[&(org, dest)] == start {
+                    break;
+                }
+            }
+        }
+        left.extend(outer);
+
+        // the dual follows from the primal, dual(onext(e).rot).onext = e.rot_inv
+        for (&e, &face) in &left {
+            let onext = mesh.get_primal(e).onext;
+            mesh.get_dual_mut(onext.rot()).onext = e.rot_inv();
+            mesh.set_dual_org(e.rot_inv(), face);
+        }
+
+        // the sides around a vertex must close up into a single onext ring
+        let mut star_sizes = HashMap::new();
+        for &(org, _) in dedges.keys() {
+            *star_sizes.entry(org).or_insert(0) += 1;
+        }
+        let mut checked = HashSet::new();
+        for polygon in polygons {
+            for (org, dest) in sides_of(polygon.as_ref()) {
+                if !checked.insert(org) {
+                    continue;
+                }
+                let size = star_sizes[&org];
+                let ring = mesh.get_primal_onext_ring(dedges[&(org, dest)]);
+                if ring.take(size + 1).count() != size {
+                    return Err(PolygonError::NonManifoldVertex(org));
+                }
+            }
+        }
+
+        Ok(mesh)
+    }
+}
+
+/// `(org, dest)` of each side of a polygon, in order.
+fn sides_of(polygon: &[usize]) -> impl Iterator<Item = (usize, usize)> + '_ {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(&org, &dest)| (org, dest))
+}
@@ -1,122 +1,74 @@
-use std::cell::RefCell;
-
 use super::{
-    quad::{DualDEdgeEntity, PrimalDEdgeEntity, PrimalDirectedEdge},
-    DualMeshCursor, Mesh,
+    quad::{PrimalDEdgeEntity, PrimalDirectedEdge},
+    DualMeshCursor, Mesh, MeshCursor, MeshExtension,
 };
 
 /// Convenience type for traversing the mesh.
-pub struct PrimalMeshCursor<'a, V, F, Cache: Default> {
-    mesh: &'a Mesh<V, F, Cache>,
+pub struct PrimalMeshCursor<'a, V, F, E, Cache: MeshExtension<V, F, E>> {
+    mesh: &'a Mesh<V, F, E, Cache>,
     entity: PrimalDEdgeEntity,
 }
 
-impl<'a, V, F, Cache: Default> PrimalMeshCursor<'a, V, F, Cache> {
-    /// Drop reference to mesh.
-    pub fn id(&self) -> PrimalDEdgeEntity {
-        self.entity.clone()
+impl<'a, V, F, E, Cache: MeshExtension<V, F, E>> Clone for PrimalMeshCursor<'a, V, F, E, Cache> {
+    fn clone(&self) -> Self {
+        *self
     }
 }
+impl<'a, V, F, E, Cache: MeshExtension<V, F, E>> Copy for PrimalMeshCursor<'a, V, F, E, Cache> {}
 
-impl<'a, V, F, Cache: Default> PrimalMeshCursor<'a, V, F, Cache> {
+impl<'a, V, F, E, Cache: MeshExtension<V, F, E>> PrimalMeshCursor<'a, V, F, E, Cache> {
     pub fn new(
-        mesh: &'a Mesh<V, F, Cache>,
+        mesh: &'a Mesh<V, F, E, Cache>,
         entity: PrimalDEdgeEntity,
-    ) -> PrimalMeshCursor<'a, V, F, Cache> {
+    ) -> PrimalMeshCursor<'a, V, F, E, Cache> {
         PrimalMeshCursor { mesh, entity }
     }
-    pub fn org(&self) -> &RefCell<V> {
+    pub fn org(&self) -> &'a V {
+        self.mesh.get_vertex(self.mesh.get_primal(self.entity).org)
+    }
+    pub fn dest(&self) -> &'a V {
         self.mesh
-            .get_vertex(self.mesh.get_primal(self.entity).borrow().org)
+            .get_vertex(self.mesh.get_primal(self.entity.sym()).org)
     }
-    pub fn dest(&self) -> &RefCell<V> {
+    pub fn left(&self) -> &'a F {
         self.mesh
-            .get_vertex(self.mesh.get_primal(self.entity.sym()).borrow().org)
+            .get_face(self.mesh.get_dual(self.entity.rot_inv()).org)
     }
-    pub fn left(&self) -> &RefCell<F> {
+    pub fn right(&self) -> &'a F {
         self.mesh
-            .get_face(self.mesh.get_dual(self.entity.rot_inv()).borrow().org)
+            .get_face(self.mesh.get_dual(self.entity.rot()).org)
     }
-    pub fn get(&self) -> &RefCell<PrimalDirectedEdge> {
+    pub fn get(&self) -> &'a PrimalDirectedEdge {
         self.mesh.get_primal(self.entity)
     }
 
-    fn extend(&self, entity: PrimalDEdgeEntity) -> PrimalMeshCursor<'a, V, F, Cache> {
+    fn extend(&self, entity: PrimalDEdgeEntity) -> PrimalMeshCursor<'a, V, F, E, Cache> {
         PrimalMeshCursor {
             mesh: self.mesh,
             entity,
         }
     }
-    fn extend_other(&self, entity: DualDEdgeEntity) -> DualMeshCursor<'a, V, F, Cache> {
-        DualMeshCursor::new(self.mesh, entity)
-    }
+}
 
-    pub fn onext(&self) -> PrimalMeshCursor<'a, V, F, Cache> {
-        self.extend(self.mesh.get_primal(self.entity).borrow().onext)
-    }
-    pub fn onext_mut(&mut self) -> &mut Self {
-        self.entity = self.mesh.get_primal(self.entity).borrow().onext;
-        self
-    }
-    pub fn oprev(&self) -> PrimalMeshCursor<'a, V, F, Cache> {
-        self.extend(self.mesh.get_dual(self.entity.rot()).borrow().onext.rot())
-    }
-    pub fn dprev(&self) -> Self {
-        self.extend(
-            self.mesh
-                .get_dual(self.entity.rot_inv())
-                .borrow()
-                .onext
-                .rot_inv(),
-        )
-    }
-    pub fn dprev_mut(&mut self) -> &mut Self {
-        self.entity = self
-            .mesh
-            .get_dual(self.entity.rot_inv())
-            .borrow()
-            .onext
-            .rot_inv();
-        self
-    }
-    pub fn lnext(&self) -> PrimalMeshCursor<'a, V, F, Cache> {
-        self.extend(
-            self.mesh
-                .get_dual(self.entity.rot_inv())
-                .borrow()
-                .onext
-                .rot(),
-        )
-    }
-    pub fn lnext_mut(&mut self) -> &mut Self {
-        self.entity = self
-            .mesh
-            .get_dual(self.entity.rot_inv())
-            .borrow()
-            .onext
-            .rot();
-        self
-    }
-    pub fn lprev(&self) -> Self {
-        self.extend(self.mesh.get_primal(self.entity).borrow().onext.sym())
-    }
-    pub fn rnext(&self) -> Self {
-        self.extend(
-            self.mesh
-                .get_dual(self.entity.rot())
-                .borrow()
-                .onext
-                .rot_inv(),
-        )
+impl<'a, V, F, E, Cache: MeshExtension<V, F, E>> MeshCursor
+    for PrimalMeshCursor<'a, V, F, E, Cache>
+{
+    type Entity = PrimalDEdgeEntity;
+    type Other = DualMeshCursor<'a, V, F, E, Cache>;
+
+    fn id(&self) -> PrimalDEdgeEntity {
+        self.entity
     }
-    pub fn rprev(&self) -> Self {
-        self.extend(self.mesh.get_primal(self.entity.sym()).borrow().onext)
+    fn onext(&self) -> Self {
+        self.extend(self.mesh.get_primal(self.entity).onext)
     }
-    pub fn sym(&self) -> PrimalMeshCursor<V, F, Cache> {
+    fn sym(&self) -> Self {
         self.extend(self.entity.sym())
     }
-    pub fn sym_mut(&mut self) -> &mut Self {
-        self.entity = self.entity.sym();
-        self
+    fn rot(&self) -> DualMeshCursor<'a, V, F, E, Cache> {
+        DualMeshCursor::new(self.mesh, self.entity.rot())
+    }
+    fn rot_inv(&self) -> DualMeshCursor<'a, V, F, E, Cache> {
+        DualMeshCursor::new(self.mesh, self.entity.rot_inv())
     }
 }
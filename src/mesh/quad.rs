@@ -1,18 +1,32 @@
-#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
-pub struct UnspecifiedDEdgeEntity(pub usize);
+/// Slot generation carried by every entity handle. See [`super::Slots`].
+pub type Generation = u32;
 
-#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
-pub struct PrimalDEdgeEntity(pub usize);
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnspecifiedDEdgeEntity(pub usize, pub Generation);
+
+/// Directed edges `2k` and `2k + 1` are the two halves of the quad-edge stored in slot `k`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PrimalDEdgeEntity(pub usize, pub Generation);
 
 impl PrimalDEdgeEntity {
+    /// The canonical dedge of the quad-edge in slot `edge`.
+    pub fn from_edge(edge: usize, generation: Generation) -> Self {
+        PrimalDEdgeEntity(edge << 1, generation)
+    }
+    /// Slot of the quad-edge this dedge belongs to.
+    pub fn edge(self) -> usize {
+        self.0 >> 1
+    }
     pub fn rot(self) -> DualDEdgeEntity {
-        DualDEdgeEntity(self.0)
+        DualDEdgeEntity(self.0, self.1)
     }
     pub fn rot_inv(self) -> DualDEdgeEntity {
-        DualDEdgeEntity(self.0 ^ 1)
+        DualDEdgeEntity(self.0 ^ 1, self.1)
     }
     pub fn sym(self) -> PrimalDEdgeEntity {
-        PrimalDEdgeEntity(self.0 ^ 1)
+        PrimalDEdgeEntity(self.0 ^ 1, self.1)
     }
     pub fn orientation(self) -> bool {
         self.0.is_power_of_two()
@@ -21,56 +35,73 @@ impl PrimalDEdgeEntity {
 
 impl From<UnspecifiedDEdgeEntity> for PrimalDEdgeEntity {
     fn from(e: UnspecifiedDEdgeEntity) -> Self {
-        PrimalDEdgeEntity(e.0)
+        PrimalDEdgeEntity(e.0, e.1)
     }
 }
 
 impl Into<UnspecifiedDEdgeEntity> for PrimalDEdgeEntity {
     fn into(self) -> UnspecifiedDEdgeEntity {
-        UnspecifiedDEdgeEntity(self.0)
+        UnspecifiedDEdgeEntity(self.0, self.1)
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
-pub struct DualDEdgeEntity(pub usize);
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DualDEdgeEntity(pub usize, pub Generation);
 
 impl DualDEdgeEntity {
+    /// Slot of the quad-edge this dedge belongs to.
+    pub fn edge(self) -> usize {
+        self.0 >> 1
+    }
     pub fn rot(self) -> PrimalDEdgeEntity {
-        PrimalDEdgeEntity(self.0 ^ 1)
+        PrimalDEdgeEntity(self.0 ^ 1, self.1)
     }
     pub fn rot_inv(self) -> PrimalDEdgeEntity {
-        PrimalDEdgeEntity(self.0)
+        PrimalDEdgeEntity(self.0, self.1)
     }
     pub fn sym(self) -> DualDEdgeEntity {
-        DualDEdgeEntity(self.0 ^ 1)
+        DualDEdgeEntity(self.0 ^ 1, self.1)
     }
 }
 
 impl From<UnspecifiedDEdgeEntity> for DualDEdgeEntity {
     fn from(e: UnspecifiedDEdgeEntity) -> Self {
-        DualDEdgeEntity(e.0)
+        DualDEdgeEntity(e.0, e.1)
     }
 }
 
 impl Into<UnspecifiedDEdgeEntity> for DualDEdgeEntity {
     fn into(self) -> UnspecifiedDEdgeEntity {
-        UnspecifiedDEdgeEntity(self.0)
+        UnspecifiedDEdgeEntity(self.0, self.1)
     }
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
-pub struct VertexEntity(pub usize);
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
-pub struct FaceEntity(pub usize);
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VertexEntity(pub usize, pub Generation);
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FaceEntity(pub usize, pub Generation);
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PrimalDirectedEdge {
     pub org: VertexEntity,
     pub onext: PrimalDEdgeEntity,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DualDirectedEdge {
     pub org: FaceEntity,
     pub onext: DualDEdgeEntity,
 }
+
+/// The four directed edges of one quad-edge, indexed by the low bit of their entity.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuadEdge {
+    pub primal: [PrimalDirectedEdge; 2],
+    pub dual: [DualDirectedEdge; 2],
+}
@@ -0,0 +1,336 @@
+use std::fmt;
+
+use super::quad::Generation;
+
+/// A single storage slot. `generation` is bumped every time the slot is freed, so handles
+/// created before the free can be told apart from handles to whatever reuses the slot.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Slot<T> {
+    pub generation: Generation,
+    pub value: Option<T>,
+}
+
+/// What a `(index, generation)` handle currently refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotStatus {
+    Live,
+    /// Reserved with a matching generation, but nothing has been inserted yet.
+    Vacant,
+    /// The slot was freed (and possibly reused) since the handle was created.
+    Stale,
+    /// The index was never allocated.
+    Dangling,
+}
+
+impl fmt::Display for SlotStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SlotStatus::Live => "live",
+            SlotStatus::Vacant => "reserved but never inserted",
+            SlotStatus::Stale => "stale (its slot was freed)",
+            SlotStatus::Dangling => "dangling (its slot was never allocated)",
+        })
+    }
+}
+
+/// Generational slot storage with free-list reuse.
+///
+/// Between [`Slots::begin`] and [`Slots::end`] every change is journaled so that it can be
+/// reverted exactly, free list included. Writes through [`Slots::get_mut`] and
+/// [`Slots::iter_mut`] are the exception, use [`Slots::edit`] for those.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Slots<T> {
+    entries: Vec<Slot<T>>,
+    free: Vec<usize>,
+    /// Generation of newly appended slots, raised by [`Slots::compact`] past every generation
+    /// handed out before it.
+    #[cfg_attr(feature = "serde", serde(default))]
+    epoch: Generation,
+    #[cfg_attr(feature = "serde", serde(skip, default = "Option::default"))]
+    journal: Option<Vec<SlotChange<T>>>,
+}
+
+impl<T> Default for Slots<T> {
+    fn default() -> Self {
+        Self {
+            entries: Default::default(),
+            free: Default::default(),
+            epoch: 0,
+            journal: None,
+        }
+    }
+}
+
+/// One journaled change. Applying it undoes the change and gives the change that redoes it.
+#[derive(Debug)]
+enum SlotChange<T> {
+    /// The slot at the index held this before.
+    Replace(usize, Slot<T>),
+    /// A slot was appended.
+    Push,
+    /// This slot was at the end and has been dropped.
+    Pop(Slot<T>),
+    /// The index was taken off the free list.
+    Unfree(usize),
+    /// An index was put on the free list.
+    Free,
+}
+
+/// Changes to a [`Slots`] recorded between [`Slots::begin`] and [`Slots::end`].
+#[derive(Debug)]
+pub struct SlotJournal<T> {
+    changes: Vec<SlotChange<T>>,
+}
+
+impl<T> SlotJournal<T> {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl<T> Slots<T> {
+    /// Number of allocated slots, including freed ones.
+    pub fn slot_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Number of slots currently holding a value.
+    pub fn live_count(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|slot| slot.value.is_some())
+            .count()
+    }
+
+    /// Claim an empty slot, reusing a freed one if possible.
+    pub fn reserve(&mut self) -> (usize, Generation) {
+        if let Some(index) = self.free.pop() {
+            self.record(|| SlotChange::Unfree(index));
+            (index, self.entries[index].generation)
+        } else {
+            self.record(|| SlotChange::Push);
+            self.entries.push(Slot {
+                generation: self.epoch,
+                value: None,
+            });
+            (self.entries.len() - 1, self.epoch)
+        }
+    }
+
+    pub fn insert(&mut self, value: T) -> (usize, Generation) {
+        self.insert_with(|_, _| value)
+    }
+
+    /// Insert a value that needs to know its own handle, e.g. a self-referencing quad-edge.
+    pub fn insert_with(&mut self, f: impl FnOnce(usize, Generation) -> T) -> (usize, Generation) {
+        let (index, generation) = self.reserve();
+        self.fill(index, generation, f(index, generation));
+        (index, generation)
+    }
+
+    /// Free the slot, dropping its value, or keeping it in the journal while recording.
+    /// Outstanding handles to it become stale. Returns whether the handle was current.
+    pub fn remove(&mut self, index: usize, generation: Generation) -> bool {
+        match self.entries.get_mut(index) {
+            Some(slot) if slot.generation == generation => {
+                let old = Slot {
+                    generation,
+                    value: slot.value.take(),
+                };
+                slot.generation = generation.wrapping_add(1);
+                self.free.push(index);
+                self.record(|| SlotChange::Replace(index, old));
+                self.record(|| SlotChange::Free);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn status(&self, index: usize, generation: Generation) -> SlotStatus {
+        match self.entries.get(index) {
+            None => SlotStatus::Dangling,
+            Some(slot) if slot.generation != generation => SlotStatus::Stale,
+            Some(Slot { value: None, .. }) => SlotStatus::Vacant,
+            Some(_) => SlotStatus::Live,
+        }
+    }
+
+    pub fn get(&self, index: usize, generation: Generation) -> Option<&T> {
+        self.entries
+            .get(index)
+            .filter(|slot| slot.generation == generation)
+            .and_then(|slot| slot.value.as_ref())
+    }
+
+    pub fn get_mut(&mut self, index: usize, generation: Generation) -> Option<&mut T> {
+        self.entries
+            .get_mut(index)
+            .filter(|slot| slot.generation == generation)
+            .and_then(|slot| slot.value.as_mut())
+    }
+
+    /// Put a value in a live or vacant slot, e.g. one from [`Slots::reserve`]. Returns whether
+    /// the handle was current.
+    pub fn fill(&mut self, index: usize, generation: Generation, value: T) -> bool {
+        match self.entries.get_mut(index) {
+            Some(slot) if slot.generation == generation => {
+                let old = slot.value.replace(value);
+                self.record(|| {
+                    SlotChange::Replace(
+                        index,
+                        Slot {
+                            generation,
+                            value: old,
+                        },
+                    )
+                });
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Live values along with their handles, mutably.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (usize, Generation, &mut T)> {
+        self.entries.iter_mut().enumerate().filter_map(|(i, slot)| {
+            let generation = slot.generation;
+            slot.value.as_mut().map(|value| (i, generation, value))
+        })
+    }
+
+    /// Drop freed slots, moving the rest down to fill the holes in their original order.
+    ///
+    /// Reserved slots are kept, since whoever reserved them still holds the handle. Every kept
+    /// slot moves on to a generation newer than any used before, as do slots appended later, so
+    /// old handles must be translated through the returned remap and are stale if they aren't.
+    pub fn compact(&mut self) -> SlotRemap {
+        assert!(self.journal.is_none(), "can't compact while recording");
+        let mut freed = vec![false; self.entries.len()];
+        for &index in &self.free {
+            freed[index] = true;
+        }
+        self.free.clear();
+        self.epoch = self
+            .entries
+            .iter()
+            .map(|slot| slot.generation)
+            .fold(self.epoch, Generation::max)
+            .wrapping_add(1);
+
+        let mut table = Vec::with_capacity(self.entries.len());
+        let mut kept = 0;
+        for (slot, freed) in self.entries.iter_mut().zip(freed) {
+            if freed {
+                table.push(None);
+            } else {
+                table.push(Some((slot.generation, kept)));
+                slot.generation = self.epoch;
+                kept += 1;
+            }
+        }
+        let mut freed = table.iter().map(Option::is_none);
+        self.entries.retain(|_| !freed.next().unwrap());
+        SlotRemap {
+            table,
+            generation: self.epoch,
+        }
+    }
+
+    /// Live values along with their handles.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, Generation, &T)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, slot)| slot.value.as_ref().map(|value| (i, slot.generation, value)))
+    }
+
+    /// Start journaling changes. Recording is not nested, beginning again keeps the changes
+    /// recorded so far.
+    pub fn begin(&mut self) {
+        self.journal.get_or_insert_with(Vec::new);
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.journal.is_some()
+    }
+
+    /// Stop journaling and return the changes made since [`Slots::begin`].
+    pub fn end(&mut self) -> SlotJournal<T> {
+        SlotJournal {
+            changes: self.journal.take().unwrap_or_default(),
+        }
+    }
+
+    /// Undo the changes in `journal`, which must be the last changes made to these slots.
+    /// Returns the journal that redoes them.
+    pub fn revert(&mut self, journal: SlotJournal<T>) -> SlotJournal<T> {
+        assert!(self.journal.is_none(), "can't revert while recording");
+        let changes = journal
+            .changes
+            .into_iter()
+            .rev()
+            .map(|change| self.apply(change))
+            .collect();
+        SlotJournal { changes }
+    }
+
+    fn apply(&mut self, change: SlotChange<T>) -> SlotChange<T> {
+        match change {
+            SlotChange::Replace(index, slot) => {
+                SlotChange::Replace(index, std::mem::replace(&mut self.entries[index], slot))
+            }
+            SlotChange::Push => SlotChange::Pop(self.entries.pop().expect("no slot to pop")),
+            SlotChange::Pop(slot) => {
+                self.entries.push(slot);
+                SlotChange::Push
+            }
+            SlotChange::Unfree(index) => {
+                self.free.push(index);
+                SlotChange::Free
+            }
+            SlotChange::Free => SlotChange::Unfree(self.free.pop().expect("no free slot to pop")),
+        }
+    }
+
+    fn record(&mut self, change: impl FnOnce() -> SlotChange<T>) {
+        if let Some(journal) = &mut self.journal {
+            journal.push(change());
+        }
+    }
+}
+
+impl<T: Clone> Slots<T> {
+    /// Like [`Slots::get_mut`], but while recording the slot is journaled first so the write
+    /// can be reverted.
+    pub fn edit(&mut self, index: usize, generation: Generation) -> Option<&mut T> {
+        if self.journal.is_some() && self.get(index, generation).is_some() {
+            let old = self.entries[index].clone();
+            self.record(|| SlotChange::Replace(index, old));
+        }
+        self.get_mut(index, generation)
+    }
+}
+
+/// Old slot handles to new slot indices, returned by [`Slots::compact`].
+#[derive(Debug, Clone, Default)]
+pub struct SlotRemap {
+    table: Vec<Option<(Generation, usize)>>,
+    /// The generation of every slot after compacting.
+    generation: Generation,
+}
+
+impl SlotRemap {
+    /// New handle of the slot `(index, generation)` referred to, or `None` if the handle was
+    /// already stale or dangling.
+    pub fn get(&self, index: usize, generation: Generation) -> Option<(usize, Generation)> {
+        match self.table.get(index) {
+            Some(&Some((old_generation, new_index))) if old_generation == generation => {
+                Some((new_index, self.generation))
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use super::{
+    quad::{DualDEdgeEntity, FaceEntity, PrimalDEdgeEntity, VertexEntity},
+    DualOnextRing, Mesh, MeshExtension, SlotStatus,
+};
+
+impl<V, F, E, Cache: MeshExtension<V, F, E>> Mesh<V, F, E, Cache> {
+    /// Every live undirected edge once, as its canonical primal dedge.
+    pub fn edges(&self) -> impl Iterator<Item = PrimalDEdgeEntity> + '_ {
+        self.quad_edges
+            .iter()
+            .map(|(edge, generation, _)| PrimalDEdgeEntity::from_edge(edge, generation))
+    }
+
+    /// Every live vertex along with its star, the onext ring of dedges leaving it.
+    ///
+    /// Isolated vertices have an empty star. A vertex labelling several onext rings (see
+    /// [`Mesh::validate`]) is yielded once, with one of them.
+    pub fn vertex_stars(
+        &self,
+    ) -> impl Iterator<Item = (VertexEntity, Vec<PrimalDEdgeEntity>)> + '_ {
+        let mut first = vec![None; self.vertices.slot_count()];
+        for e in self.primal_dedges() {
+            let org = self.get_primal(e).org;
+            if self.vertices.status(org.0, org.1) == SlotStatus::Live {
+                first[org.0].get_or_insert(e);
+            }
+        }
+        self.vertices.iter().map(move |(index, generation, _)| {
+            let star = first[index]
+                .map(|e| self.get_primal_onext_ring(e).collect())
+                .unwrap_or_default();
+            (VertexEntity(index, generation), star)
+        })
+    }
+
+    /// Every live face along with its boundary, the lnext ring of dedges with it on their left.
+    ///
+    /// Faces no dedge refers to have an empty boundary. A face labelling several lnext rings
+    /// (see [`Mesh::validate`]) is yielded once, with one of them.
+    pub fn face_boundaries(
+        &self,
+    ) -> impl Iterator<Item = (FaceEntity, Vec<PrimalDEdgeEntity>)> + '_ {
+        let mut first = vec![None; self.faces.slot_count()];
+        for e in self.primal_dedges() {
+            let left = self.get_dual(e.rot_inv()).org;
+            if self.faces.status(left.0, left.1) == SlotStatus::Live {
+                first[left.0].get_or_insert(e);
+            }
+        }
+        self.faces.iter().map(move |(index, generation, _)| {
+            let boundary = first[index]
+                .map(|e| self.get_lnext_ring(e).collect())
+                .unwrap_or_default();
+            (FaceEntity(index, generation), boundary)
+        })
+    }
+
+    /// The dedges around the left face of `entity`, starting with `entity`.
+    pub fn get_lnext_ring(&self, entity: PrimalDEdgeEntity) -> PrimalLnextRing<'_, V, F, E, Cache> {
+        PrimalLnextRing {
+            ring: self.get_dual_onext_ring(entity.rot_inv()),
+        }
+    }
+
+    /// Destinations of the onext ring of `entity`, i.e. the vertices adjacent to its origin.
+    pub fn get_vertex_neighbors(
+        &self,
+        entity: PrimalDEdgeEntity,
+    ) -> impl Iterator<Item = VertexEntity> + '_ {
+        self.get_primal_onext_ring(entity)
+            .map(move |e| self.get_primal(e.sym()).org)
+    }
+
+    /// Faces across each edge of the lnext ring of `entity`, i.e. the faces adjacent to its
+    /// left face.
+    pub fn get_face_neighbors(
+        &self,
+        entity: PrimalDEdgeEntity,
+    ) -> impl Iterator<Item = FaceEntity> + '_ {
+        self.get_lnext_ring(entity)
+            .map(move |e| self.get_dual(e.rot()).org)
+    }
+}
+
+/// Primal dedges sharing a left face, in lnext order. The dual onext ring of the face's
+/// dual vertex.
+pub struct PrimalLnextRing<'a, V, F, E, Cache: MeshExtension<V, F, E>> {
+    ring: DualOnextRing<'a, V, F, E, Cache>,
+}
+
+impl<'a, V, F, E, Cache: MeshExtension<V, F, E>> Iterator for PrimalLnextRing<'a, V, F, E, Cache> {
+    type Item = PrimalDEdgeEntity;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.ring.next().map(DualDEdgeEntity::rot)
+    }
+}
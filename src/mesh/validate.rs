@@ -0,0 +1,307 @@
+use std::{collections::HashMap, fmt};
+
+use super::{
+    quad::{DualDEdgeEntity, FaceEntity, PrimalDEdgeEntity, VertexEntity},
+    EntityRef, Mesh, MeshExtension, SlotStatus,
+};
+
+/// A broken structural invariant found by [`Mesh::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// `onext` points at a slot that is not live.
+    DanglingOnext { dedge: EntityRef, onext: EntityRef },
+    /// `dedge.Oprev.Onext != dedge`, i.e. the primal and dual `onext` pointers disagree.
+    BrokenRotPairing(EntityRef),
+    /// Following `onext` from `dedge` never returns to it.
+    OpenOnextRing(EntityRef),
+    /// A primal dedge and its `onext` have different origins.
+    OrgMismatch {
+        dedge: PrimalDEdgeEntity,
+        org: VertexEntity,
+        onext_org: VertexEntity,
+    },
+    /// A dual dedge and its `onext` carry different faces, so one lnext ring has two labels.
+    FaceLabelMismatch {
+        dedge: DualDEdgeEntity,
+        face: FaceEntity,
+        onext_face: FaceEntity,
+    },
+    /// The label of a dedge is not a live vertex or face.
+    DeadLabel { dedge: EntityRef, label: EntityRef },
+    /// Two different onext rings carry the same vertex.
+    SharedVertexLabel(VertexEntity),
+    /// Two different lnext rings carry the same face.
+    SharedFaceLabel(FaceEntity),
+    /// `V - E + F` differs from `2C` for `C` connected components, so some component is not a
+    /// sphere. Only checked by [`Mesh::validate_planar`].
+    EulerCharacteristic {
+        vertices: usize,
+        edges: usize,
+        faces: usize,
+        components: usize,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::DanglingOnext { dedge, onext } => {
+                write!(f, "{}.onext = {} is not live", dedge, onext)
+            }
+            Violation::BrokenRotPairing(e) => write!(f, "{}.oprev.onext != {}", e, e),
+            Violation::OpenOnextRing(e) => write!(f, "onext ring of {} is not closed", e),
+            Violation::OrgMismatch {
+                dedge,
+                org,
+                onext_org,
+            } => write!(
+                f,
+                "{:?}.org = {:?} but {:?}.onext.org = {:?}",
+                dedge, org, dedge, onext_org
+            ),
+            Violation::FaceLabelMismatch {
+                dedge,
+                face,
+                onext_face,
+            } => write!(
+                f,
+                "{:?}.org = {:?} but {:?}.onext.org = {:?}",
+                dedge, face, dedge, onext_face
+            ),
+            Violation::DeadLabel { dedge, label } => {
+                write!(f, "{}.org = {} is not live", dedge, label)
+            }
+            Violation::SharedVertexLabel(v) => write!(f, "{:?} labels several onext rings", v),
+            Violation::SharedFaceLabel(face) => {
+                write!(f, "{:?} labels several lnext rings", face)
+            }
+            Violation::EulerCharacteristic {
+                vertices,
+                edges,
+                faces,
+                components,
+            } => write!(
+                f,
+                "V - E + F = {} - {} + {} but there are {} components",
+                vertices, edges, faces, components
+            ),
+        }
+    }
+}
+
+impl<V, F, E, Cache: MeshExtension<V, F, E>> Mesh<V, F, E, Cache> {
+    /// Walk every live quad-edge and report every broken invariant.
+    ///
+    /// Checks that `onext` pointers are live, closed and consistent between the primal and dual
+    /// halves, and that rings agree on their vertex and face labels. These hold for a surface
+    /// of any genus, such as a torus read from a polygon file.
+    pub fn validate(&self) -> Result<(), Vec<Violation>> {
+        self.check(false)
+    }
+
+    /// [`Mesh::validate`], and also that every component is a sphere, or a planar subdivision
+    /// like a Delaunay mesh: `V - E + F = 2C` where `V` and `F` count onext and lnext rings and
+    /// `C` counts connected components.
+    pub fn validate_planar(&self) -> Result<(), Vec<Violation>> {
+        self.check(true)
+    }
+
+    fn check(&self, planar: bool) -> Result<(), Vec<Violation>> {
+        let mut violations = Vec::new();
+        let edge_count = self.quad_edges.live_count();
+
+        let is_live =
+            |edge: usize, generation| self.quad_edges.status(edge, generation) == SlotStatus::Live;
+
+        // pointer checks. Later checks follow pointers so only run them on a sound graph.
+        for e in self.primal_dedges() {
+            let d = e.rot();
+            let onext = self.get_primal(e).onext;
+            if !is_live(onext.edge(), onext.1) {
+                violations.push(Violation::DanglingOnext {
+                    dedge: e.into(),
+                    onext: onext.into(),
+                });
+            }
+            let dual_onext = self.get_dual(d).onext;
+            if !is_live(dual_onext.edge(), dual_onext.1) {
+                violations.push(Violation::DanglingOnext {
+                    dedge: d.into(),
+                    onext: dual_onext.into(),
+                });
+            }
+        }
+        if !violations.is_empty() {
+            return Err(violations);
+        }
+
+        for e in self.primal_dedges() {
+            let oprev = self.get_dual(e.rot()).onext.rot();
+            if self.get_primal(oprev).onext != e {
+                violations.push(Violation::BrokenRotPairing(e.into()));
+            }
+            let d = e.rot();
+            let dual_oprev = self.get_primal(d.rot()).onext.rot();
+            if self.get_dual(dual_oprev).onext != d {
+                violations.push(Violation::BrokenRotPairing(d.into()));
+            }
+        }
+        if !violations.is_empty() {
+            return Err(violations);
+        }
+
+        // every ring is visited from its lowest dedge, so rings are counted once
+        let mut vertex_rings = HashMap::new();
+        let mut face_rings = HashMap::new();
+        let mut vertex_ring_count = 0;
+        let mut face_ring_count = 0;
+        let mut components = UnionFind::new(self.quad_edges.slot_count());
+
+        for e in self.primal_dedges() {
+            let dedge = self.get_primal(e);
+            let onext = self.get_primal(dedge.onext);
+            if dedge.org != onext.org {
+                violations.push(Violation::OrgMismatch {
+                    dedge: e,
+                    org: dedge.org,
+                    onext_org: onext.org,
+                });
+            }
+            if self.vertices.status(dedge.org.0, dedge.org.1) != SlotStatus::Live {
+                violations.push(Violation::DeadLabel {
+                    dedge: e.into(),
+                    label: dedge.org.into(),
+                });
+            }
+            components.union(e.edge(), dedge.onext.edge());
+
+            let d = e.rot();
+            let dual = self.get_dual(d);
+            let dual_onext = self.get_dual(dual.onext);
+            if dual.org != dual_onext.org {
+                violations.push(Violation::FaceLabelMismatch {
+                    dedge: d,
+                    face: dual.org,
+                    onext_face: dual_onext.org,
+                });
+            }
+            if self.faces.status(dual.org.0, dual.org.1) != SlotStatus::Live {
+                violations.push(Violation::DeadLabel {
+                    dedge: d.into(),
+                    label: dual.org.into(),
+                });
+            }
+
+            match self.primal_ring_minimum(e, edge_count) {
+                None => violations.push(Violation::OpenOnextRing(e.into())),
+                Some(min) if min == e.0 => {
+                    vertex_ring_count += 1;
+                    *vertex_rings.entry(dedge.org).or_insert(0) += 1;
+                }
+                Some(_) => (),
+            }
+            match self.dual_ring_minimum(d, edge_count) {
+                None => violations.push(Violation::OpenOnextRing(d.into())),
+                Some(min) if min == d.0 => {
+                    face_ring_count += 1;
+                    *face_rings.entry(dual.org).or_insert(0) += 1;
+                }
+                Some(_) => (),
+            }
+        }
+
+        let mut shared_vertices = vertex_rings
+            .into_iter()
+            .filter(|(_, rings)| *rings > 1)
+            .map(|(v, _)| v)
+            .collect::<Vec<_>>();
+        shared_vertices.sort_by_key(|v| v.0);
+        violations.extend(
+            shared_vertices
+                .into_iter()
+                .map(Violation::SharedVertexLabel),
+        );
+        let mut shared_faces = face_rings
+            .into_iter()
+            .filter(|(_, rings)| *rings > 1)
+            .map(|(face, _)| face)
+            .collect::<Vec<_>>();
+        shared_faces.sort_by_key(|face| face.0);
+        violations.extend(shared_faces.into_iter().map(Violation::SharedFaceLabel));
+
+        let component_count = self
+            .quad_edges
+            .iter()
+            .filter(|(edge, _, _)| components.find(*edge) == *edge)
+            .count();
+        if planar && vertex_ring_count + face_ring_count != edge_count + 2 * component_count {
+            violations.push(Violation::EulerCharacteristic {
+                vertices: vertex_ring_count,
+                edges: edge_count,
+                faces: face_ring_count,
+                components: component_count,
+            });
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// Lowest dedge index in the primal onext ring of `e`, or `None` if the ring does not close
+    /// within `limit` steps.
+    fn primal_ring_minimum(&self, e: PrimalDEdgeEntity, limit: usize) -> Option<usize> {
+        let mut min = e.0;
+        let mut current = self.get_primal(e).onext;
+        for _ in 0..2 * limit {
+            if current == e {
+                return Some(min);
+            }
+            min = min.min(current.0);
+            current = self.get_primal(current).onext;
+        }
+        None
+    }
+
+    /// Lowest dedge index in the dual onext ring of `d`, or `None` if the ring does not close
+    /// within `limit` steps.
+    fn dual_ring_minimum(&self, d: DualDEdgeEntity, limit: usize) -> Option<usize> {
+        let mut min = d.0;
+        let mut current = self.get_dual(d).onext;
+        for _ in 0..2 * limit {
+            if current == d {
+                return Some(min);
+            }
+            min = min.min(current.0);
+            current = self.get_dual(current).onext;
+        }
+        None
+    }
+}
+
+/// Connected components over quad-edge slots.
+struct UnionFind {
+    parent: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+        }
+    }
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+    fn union(&mut self, a: usize, b: usize) {
+        let a = self.find(a);
+        let b = self.find(b);
+        self.parent[a] = b;
+    }
+}
@@ -0,0 +1,244 @@
+//! Adaptive exact orientation and incircle tests, after Shewchuk's "Adaptive Precision
+//! Floating-Point Arithmetic and Fast Robust Geometric Predicates".
+//!
+//! Each test first evaluates its determinant in `f64` and returns that when it is larger than
+//! a bound on the rounding error. Otherwise it is evaluated again exactly, as an expansion: a
+//! sum of `f64` components that don't overlap, whose sign is the sign of its largest
+//! component. Coordinates are widened to `f64` exactly, and the expansions are exact barring
+//! overflow or underflow, so the sign returned is always the sign of the exact determinant.
+//!
+//! Integer coordinates, which `f64` can't hold exactly past 2^53, have their own tests that
+//! evaluate the determinants in 320 bit integer arithmetic, with no rounding at all.
+
+use std::{
+    cmp::Ordering,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+use cgmath::Point2;
+
+/// Half an ulp of 1.0, the relative error of one rounded `f64` operation.
+const EPSILON: f64 = f64::EPSILON / 2.0;
+const CCW_ERROR_BOUND: f64 = (3.0 + 16.0 * EPSILON) * EPSILON;
+const INCIRCLE_ERROR_BOUND: f64 = (10.0 + 96.0 * EPSILON) * EPSILON;
+
+/// Positive if `a`, `b` and `c` turn counterclockwise, negative if clockwise and zero if they
+/// are collinear. The magnitude approximates twice the area of the triangle.
+pub fn orient2d<S: Into<f64> + Copy>(a: Point2<S>, b: Point2<S>, c: Point2<S>) -> f64 {
+    let [ax, ay, bx, by, cx, cy] = [a.x, a.y, b.x, b.y, c.x, c.y].map(Into::into);
+    let left = (ax - cx) * (by - cy);
+    let right = (ay - cy) * (bx - cx);
+    let det = left - right;
+    if det.abs() > CCW_ERROR_BOUND * (left.abs() + right.abs()) {
+        return det;
+    }
+
+    let (acx, acy) = (two_diff(ax, cx), two_diff(ay, cy));
+    let (bcx, bcy) = (two_diff(bx, cx), two_diff(by, cy));
+    let det = expansion_diff(&product(&acx, &bcy), &product(&acy, &bcx));
+    estimate(&det)
+}
+
+/// Positive if `d` is inside the circle through `a`, `b` and `c`, negative if outside and zero
+/// if the four are cocircular, provided `a`, `b` and `c` turn counterclockwise. The signs swap
+/// if they turn clockwise.
+pub fn incircle<S: Into<f64> + Copy>(
+    a: Point2<S>,
+    b: Point2<S>,
+    c: Point2<S>,
+    d: Point2<S>,
+) -> f64 {
+    let [ax, ay, bx, by, cx, cy, dx, dy] = [a.x, a.y, b.x, b.y, c.x, c.y, d.x, d.y].map(Into::into);
+    let (adx, ady) = (ax - dx, ay - dy);
+    let (bdx, bdy) = (bx - dx, by - dy);
+    let (cdx, cdy) = (cx - dx, cy - dy);
+
+    let (bdxcdy, cdxbdy) = (bdx * cdy, cdx * bdy);
+    let (cdxady, adxcdy) = (cdx * ady, adx * cdy);
+    let (adxbdy, bdxady) = (adx * bdy, bdx * ady);
+    let alift = adx * adx + ady * ady;
+    let blift = bdx * bdx + bdy * bdy;
+    let clift = cdx * cdx + cdy * cdy;
+    let det = alift * (bdxcdy - cdxbdy) + blift * (cdxady - adxcdy) + clift * (adxbdy - bdxady);
+    let permanent = (bdxcdy.abs() + cdxbdy.abs()) * alift
+        + (cdxady.abs() + adxcdy.abs()) * blift
+        + (adxbdy.abs() + bdxady.abs()) * clift;
+    if det.abs() > INCIRCLE_ERROR_BOUND * permanent {
+        return det;
+    }
+
+    let (adx, ady) = (two_diff(ax, dx), two_diff(ay, dy));
+    let (bdx, bdy) = (two_diff(bx, dx), two_diff(by, dy));
+    let (cdx, cdy) = (two_diff(cx, dx), two_diff(cy, dy));
+    let lift = |x: &[f64], y: &[f64]| expansion_sum(&product(x, x), &product(y, y));
+    let cross = |x1: &[f64], y1: &[f64], x2: &[f64], y2: &[f64]| {
+        expansion_diff(&product(x1, y2), &product(y1, x2))
+    };
+    let det = expansion_sum(
+        &expansion_sum(
+            &product(&lift(&adx, &ady), &cross(&bdx, &bdy, &cdx, &cdy)),
+            &product(&lift(&bdx, &bdy), &cross(&cdx, &cdy, &adx, &ady)),
+        ),
+        &product(&lift(&cdx, &cdy), &cross(&adx, &ady, &bdx, &bdy)),
+    );
+    estimate(&det)
+}
+
+/// The sign of [`orient2d`] for integer coordinates, computed exactly.
+pub fn orient2d_integer(a: Point2<i64>, b: Point2<i64>, c: Point2<i64>) -> Ordering {
+    let (acx, acy) = (Wide::diff(a.x, c.x), Wide::diff(a.y, c.y));
+    let (bcx, bcy) = (Wide::diff(b.x, c.x), Wide::diff(b.y, c.y));
+    (acx * bcy - acy * bcx).signum()
+}
+
+/// The sign of [`incircle`] for integer coordinates, computed exactly.
+pub fn incircle_integer(
+    a: Point2<i64>,
+    b: Point2<i64>,
+    c: Point2<i64>,
+    d: Point2<i64>,
+) -> Ordering {
+    let (adx, ady) = (Wide::diff(a.x, d.x), Wide::diff(a.y, d.y));
+    let (bdx, bdy) = (Wide::diff(b.x, d.x), Wide::diff(b.y, d.y));
+    let (cdx, cdy) = (Wide::diff(c.x, d.x), Wide::diff(c.y, d.y));
+    let lift = |x: Wide, y: Wide| x * x + y * y;
+    let det = lift(adx, ady) * (bdx * cdy - cdx * bdy)
+        + lift(bdx, bdy) * (cdx * ady - adx * cdy)
+        + lift(cdx, cdy) * (adx * bdy - bdx * ady);
+    det.signum()
+}
+
+/// A 320 bit two's complement integer, least significant limb first. Differences of `i64`
+/// coordinates take 65 bits, so the incircle determinant takes at most 262.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Wide([u64; 5]);
+
+impl Wide {
+    fn new(x: i128) -> Self {
+        let fill = if x < 0 { u64::MAX } else { 0 };
+        Wide([x as u64, (x >> 64) as u64, fill, fill, fill])
+    }
+
+    /// `a - b`, which can't overflow once widened.
+    fn diff(a: i64, b: i64) -> Self {
+        Wide::new(a as i128 - b as i128)
+    }
+
+    fn signum(self) -> Ordering {
+        if self.0[4] >> 63 == 1 {
+            Ordering::Less
+        } else if self.0 == [0; 5] {
+            Ordering::Equal
+        } else {
+            Ordering::Greater
+        }
+    }
+}
+
+impl Add for Wide {
+    type Output = Wide;
+    fn add(self, other: Wide) -> Wide {
+        let mut sum = [0; 5];
+        let mut carry = false;
+        for (i, limb) in sum.iter_mut().enumerate() {
+            let (s, c1) = self.0[i].overflowing_add(other.0[i]);
+            let (s, c2) = s.overflowing_add(carry as u64);
+            *limb = s;
+            carry = c1 || c2;
+        }
+        Wide(sum)
+    }
+}
+
+impl Neg for Wide {
+    type Output = Wide;
+    fn neg(self) -> Wide {
+        Wide(self.0.map(|limb| !limb)) + Wide::new(1)
+    }
+}
+
+impl Sub for Wide {
+    type Output = Wide;
+    fn sub(self, other: Wide) -> Wide {
+        self + -other
+    }
+}
+
+/// The product modulo 2^320, which for two's complement is the signed product whenever that
+/// fits.
+impl Mul for Wide {
+    type Output = Wide;
+    fn mul(self, other: Wide) -> Wide {
+        let mut product = [0; 5];
+        for i in 0..5 {
+            let mut carry = 0;
+            for j in 0..5 - i {
+                let t = self.0[i] as u128 * other.0[j] as u128 + product[i + j] as u128 + carry;
+                product[i + j] = t as u64;
+                carry = t >> 64;
+            }
+        }
+        Wide(product)
+    }
+}
+
+/// `a + b` as the rounded sum and its rounding error.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let b_virtual = x - a;
+    let a_virtual = x - b_virtual;
+    (x, (a - a_virtual) + (b - b_virtual))
+}
+
+/// `a - b` as a two component expansion, smallest first.
+fn two_diff(a: f64, b: f64) -> [f64; 2] {
+    let (x, y) = two_sum(a, -b);
+    [y, x]
+}
+
+/// `a * b` as a two component expansion, smallest first. The fused multiply-add gives the
+/// rounding error of the product exactly.
+fn two_product(a: f64, b: f64) -> [f64; 2] {
+    let x = a * b;
+    [a.mul_add(b, -x), x]
+}
+
+/// Sum of two expansions, dropping zero components.
+fn expansion_sum(e: &[f64], f: &[f64]) -> Vec<f64> {
+    let mut sum = e.to_vec();
+    for &component in f {
+        let mut q = component;
+        let mut grown = Vec::with_capacity(sum.len() + 1);
+        for &s in &sum {
+            let (x, y) = two_sum(q, s);
+            if y != 0.0 {
+                grown.push(y);
+            }
+            q = x;
+        }
+        grown.push(q);
+        sum = grown;
+    }
+    sum
+}
+
+fn expansion_diff(e: &[f64], f: &[f64]) -> Vec<f64> {
+    let negated = f.iter().map(|x| -x).collect::<Vec<_>>();
+    expansion_sum(e, &negated)
+}
+
+/// Product of two expansions, as the sum of the products of their components.
+fn product(e: &[f64], f: &[f64]) -> Vec<f64> {
+    let mut product = vec![];
+    for &a in e {
+        for &b in f {
+            product = expansion_sum(&product, &two_product(a, b));
+        }
+    }
+    product
+}
+
+/// The value of an expansion to within rounding, with the exact sign.
+fn estimate(e: &[f64]) -> f64 {
+    e.iter().sum()
+}
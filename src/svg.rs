@@ -0,0 +1,234 @@
+//! Static SVG pictures of a [`DelaunayMesh`], for looking at meshes without the GUI.
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use cgmath::{EuclideanSpace, InnerSpace, Point2, Vector2};
+
+use crate::{
+    delaunay_voronoi::DelaunayMesh,
+    geometry::{circumcenter, Scalar},
+    mesh::{quad::FaceEntity, MeshCursor},
+};
+
+/// What to draw. The picture covers the mesh's bounding box, with `y` pointing up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgOptions {
+    /// Width of the picture in pixels. The height follows the aspect ratio of the mesh.
+    pub width: f32,
+    pub vertices: bool,
+    /// Voronoi edges between circumcenters, and rays out of the hull.
+    pub voronoi: bool,
+    pub circumcircles: bool,
+    /// Label every dedge with its id, as listed in the GUI's "Primal DEdges" panel. Labels sit
+    /// on the left of their dedge.
+    pub edge_labels: bool,
+    pub vertex_labels: bool,
+    pub face_labels: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            width: 800.0,
+            vertices: true,
+            voronoi: true,
+            circumcircles: false,
+            edge_labels: false,
+            vertex_labels: false,
+            face_labels: false,
+        }
+    }
+}
+
+impl<S: Scalar> DelaunayMesh<S> {
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let mut svg = Vec::new();
+        self.write_svg(&mut svg, options)
+            .expect("writing to a Vec can't fail");
+        String::from_utf8(svg).expect("svg output is utf-8")
+    }
+
+    /// Write the mesh as an SVG document. Elements are emitted in slot order, so the same mesh
+    /// always gives the same text. Drawing is done in `f64` whatever the mesh's coordinates.
+    pub fn write_svg(&self, mut w: impl Write, options: &SvgOptions) -> io::Result<()> {
+        let position = |p: &Point2<S>| Point2::new(p.x.as_f64(), p.y.as_f64());
+        let mut min = Point2::new(0.0, 0.0);
+        let mut max = Point2::new(1.0, 1.0);
+        for (i, (_, _, p)) in self.vertices.iter().enumerate() {
+            let p = position(p);
+            if i == 0 {
+                (min, max) = (p, p);
+            }
+            min = Point2::new(min.x.min(p.x), min.y.min(p.y));
+            max = Point2::new(max.x.max(p.x), max.y.max(p.y));
+        }
+        let diagonal = (max - min).magnitude().max(1.0);
+        let margin = diagonal * 0.05;
+        let unit = diagonal / 400.0;
+        let (width, height) = (max.x - min.x + 2.0 * margin, max.y - min.y + 2.0 * margin);
+        let to_svg = |p: Point2<f64>| Point2::new(p.x - min.x + margin, max.y + margin - p.y);
+
+        // finite triangles and their circumcenters
+        let mut triangles = Vec::new();
+        let mut centers = HashMap::new();
+        for (face, boundary) in self.face_boundaries() {
+            if boundary.len() != 3 || self.get_face(face).is_infinite() {
+                continue;
+            }
+            let corners = [0, 1, 2].map(|i| position(self.primal(boundary[i]).org()));
+            centers.insert(face, circumcenter(corners[0], corners[1], corners[2]));
+            triangles.push((face, corners));
+        }
+
+        writeln!(
+            w,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+            num(options.width as f64),
+            num(options.width as f64 * height / width),
+            num(width),
+            num(height)
+        )?;
+
+        if options.circumcircles {
+            writeln!(
+                w,
+                r##"<g class="circumcircles" fill="none" stroke="#bbbbbb" stroke-width="{}">"##,
+                num(unit)
+            )?;
+            for (face, corners) in &triangles {
+                let center = centers[face];
+                writeln!(
+                    w,
+                    r#"<circle cx="{}" cy="{}" r="{}"/>"#,
+                    num(to_svg(center).x),
+                    num(to_svg(center).y),
+                    num((corners[0] - center).magnitude())
+                )?;
+            }
+            writeln!(w, "</g>")?;
+        }
+
+        if options.voronoi {
+            writeln!(
+                w,
+                r##"<g class="voronoi" stroke="#1f77b4" stroke-width="{}" stroke-dasharray="{} {}">"##,
+                num(unit * 2.0),
+                num(unit * 8.0),
+                num(unit * 4.0)
+            )?;
+            for e in self.edges() {
+                let e = self.primal(e);
+                let left = self.get_dual(e.id().rot_inv()).org;
+                let right = self.get_dual(e.id().rot()).org;
+                let along = position(e.dest()) - position(e.org());
+                let (from, to) = match (centers.get(&left), centers.get(&right)) {
+                    (Some(&l), Some(&r)) => (l, r),
+                    // rays out of the hull, away from the finite side
+                    (Some(&l), None) if e.right().is_infinite() => (
+                        l,
+                        l + Vector2::new(along.y, -along.x).normalize() * diagonal,
+                    ),
+                    (None, Some(&r)) if e.left().is_infinite() => (
+                        r,
+                        r + Vector2::new(-along.y, along.x).normalize() * diagonal,
+                    ),
+                    _ => continue,
+                };
+                line(&mut w, to_svg(from), to_svg(to))?;
+            }
+            writeln!(w, "</g>")?;
+        }
+
+        writeln!(
+            w,
+            r#"<g class="edges" stroke="black" stroke-width="{}">"#,
+            num(unit * 2.0)
+        )?;
+        for e in self.edges() {
+            let e = self.primal(e);
+            line(
+                &mut w,
+                to_svg(position(e.org())),
+                to_svg(position(e.dest())),
+            )?;
+        }
+        writeln!(w, "</g>")?;
+
+        if options.vertices {
+            writeln!(w, r#"<g class="vertices" fill="black">"#)?;
+            for (_, _, p) in self.vertices.iter() {
+                let p = to_svg(position(p));
+                writeln!(
+                    w,
+                    r#"<circle cx="{}" cy="{}" r="{}"/>"#,
+                    num(p.x),
+                    num(p.y),
+                    num(unit * 4.0)
+                )?;
+            }
+            writeln!(w, "</g>")?;
+        }
+
+        if options.edge_labels || options.vertex_labels || options.face_labels {
+            writeln!(
+                w,
+                r#"<g class="labels" font-family="monospace" font-size="{}" text-anchor="middle" dominant-baseline="central">"#,
+                num(unit * 12.0)
+            )?;
+            if options.edge_labels {
+                for e in self.primal_dedges() {
+                    let e = self.primal(e);
+                    let (org, dest) = (position(e.org()), position(e.dest()));
+                    let along = dest - org;
+                    let left = Vector2::new(-along.y, along.x).normalize_to(unit * 10.0);
+                    let at = org.midpoint(dest) + left;
+                    label(&mut w, to_svg(at), "#cc0000", &e.id().0.to_string())?;
+                }
+            }
+            if options.vertex_labels {
+                for (index, _, p) in self.vertices.iter() {
+                    let at = to_svg(position(p)) + Vector2::new(unit * 10.0, -unit * 10.0);
+                    label(&mut w, at, "black", &format!("v{}", index))?;
+                }
+            }
+            if options.face_labels {
+                for (FaceEntity(index, _), corners) in &triangles {
+                    let at = Point2::centroid(corners);
+                    label(&mut w, to_svg(at), "#1f77b4", &format!("f{}", index))?;
+                }
+            }
+            writeln!(w, "</g>")?;
+        }
+
+        writeln!(w, "</svg>")
+    }
+}
+
+fn num(x: f64) -> String {
+    format!("{:.3}", x)
+}
+
+fn line(w: &mut impl Write, from: Point2<f64>, to: Point2<f64>) -> io::Result<()> {
+    writeln!(
+        w,
+        r#"<line x1="{}" y1="{}" x2="{}" y2="{}"/>"#,
+        num(from.x),
+        num(from.y),
+        num(to.x),
+        num(to.y)
+    )
+}
+
+fn label(w: &mut impl Write, at: Point2<f64>, fill: &str, text: &str) -> io::Result<()> {
+    writeln!(
+        w,
+        r#"<text x="{}" y="{}" fill="{}">{}</text>"#,
+        num(at.x),
+        num(at.y),
+        fill,
+        text
+    )
+}
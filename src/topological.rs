@@ -1,3 +1,3 @@
 use crate::mesh::Mesh;
 
-pub type TopologicalMesh<T> = Mesh<T, T, ()>;
+pub type TopologicalMesh<T> = Mesh<T, T, (), ()>;
@@ -0,0 +1,190 @@
+//! Voronoi cells of the sites of a [`DelaunayMesh`], clipped to a convex region.
+
+use cgmath::{
+    num_traits::{Float, Zero},
+    BaseFloat, EuclideanSpace, InnerSpace, Point2, Vector2,
+};
+
+use crate::{
+    delaunay_voronoi::DelaunayMesh,
+    geometry::Scalar,
+    mesh::{
+        quad::{PrimalDEdgeEntity, VertexEntity},
+        MeshCursor,
+    },
+};
+
+/// The Voronoi cell of one site, the points closer to it than to any other site, cut down to a
+/// clip region.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoronoiCell<S = f32> {
+    pub site: VertexEntity,
+    /// Corners counterclockwise. Empty if the cell misses the clip region.
+    pub polygon: Vec<Point2<S>>,
+    /// The sites whose cells share an edge with this one inside the clip region,
+    /// counterclockwise. Each is at the other end of one of the site's Delaunay edges.
+    pub neighbors: Vec<VertexEntity>,
+    /// Whether the cell reaches infinity before clipping, as it does for sites on the hull.
+    pub unbounded: bool,
+}
+
+impl<S: BaseFloat> VoronoiCell<S> {
+    pub fn area(&self) -> S {
+        polygon_area(&self.polygon)
+    }
+}
+
+/// The corners of the axis-aligned rectangle from `min` to `max`, counterclockwise, for use as
+/// a clip region.
+pub fn clip_rectangle<S: BaseFloat>(min: Point2<S>, max: Point2<S>) -> [Point2<S>; 4] {
+    [
+        min,
+        Point2::new(max.x, min.y),
+        max,
+        Point2::new(min.x, max.y),
+    ]
+}
+
+impl<S: Scalar> DelaunayMesh<S> {
+    /// The cell of the origin of `e`, clipped to the convex polygon `clip`, whose corners are
+    /// counterclockwise. Cells are in the float type of the coordinates, [`Scalar::Real`].
+    ///
+    /// The corners of the cell are the [`VoronoiVertex`] values of the faces around the site,
+    /// in order, so they must be up to date. A site on the hull has two rays out towards
+    /// [`VoronoiVertex::Infinite`] instead, which end beyond `clip`, and the cell is then cut
+    /// down to `clip`. A site whose faces are all infinite, on a line of sites, has no Voronoi
+    /// vertices, and its cell is `clip` cut by the bisectors with its neighbors.
+    ///
+    /// [`VoronoiVertex`]: crate::delaunay_voronoi::VoronoiVertex
+    /// [`VoronoiVertex::Infinite`]: crate::delaunay_voronoi::VoronoiVertex::Infinite
+    pub fn voronoi_cell(
+        &self,
+        e: PrimalDEdgeEntity,
+        clip: &[Point2<S::Real>],
+    ) -> VoronoiCell<S::Real> {
+        let site = self.get_primal(e).org;
+        let at = self.get_vertex(site).map(S::to_real);
+        // each spoke with the site at its end, the offset to it, and the Voronoi vertex of the
+        // face between it and the next spoke
+        let spokes = self
+            .get_primal_onext_ring(e)
+            .map(|spoke| {
+                let spoke = self.primal(spoke);
+                let offset = spoke.dest().map(S::to_real) - at;
+                (spoke.sym().get().org, offset, spoke.left().position())
+            })
+            .collect::<Vec<_>>();
+        let n = spokes.len();
+        let infinite = spokes
+            .iter()
+            .filter(|(_, _, corner)| corner.is_none())
+            .count();
+
+        let mut polygon;
+        if infinite == n {
+            polygon = clip.iter().map(|&p| (p, None)).collect::<Vec<_>>();
+            for &(neighbor, offset, _) in &spokes {
+                // keep the side of the bisector nearer the site
+                let midpoint = at.midpoint(at + offset);
+                let inside = |p: Point2<_>| (midpoint - p).dot(offset);
+                polygon = clip_half_plane(&polygon, inside, Some(neighbor));
+            }
+        } else {
+            debug_assert!(infinite <= 1, "the infinite face meets {:?} twice", site);
+            // start after the infinite face, so that the rays come last
+            let first = spokes
+                .iter()
+                .position(|(_, _, corner)| corner.is_none())
+                .map_or(0, |k| k + 1);
+            polygon = Vec::new();
+            for i in first..first + n {
+                let next = spokes[(i + 1) % n].0;
+                if let Some(corner) = spokes[i % n].2 {
+                    polygon.push((corner, Some(next)));
+                    continue;
+                }
+                // the rays run out from the Voronoi vertices either side, perpendicular to the
+                // hull edges, and are joined around the far side of `clip`
+                let reach = clip
+                    .iter()
+                    .chain(polygon.iter().map(|(corner, _)| corner))
+                    .map(|p| (p - at).magnitude())
+                    .fold(S::Real::zero(), Float::max);
+                let reach = reach + reach;
+                let (before, after) = (spokes[i % n].1, spokes[(i + 1) % n].1);
+                let out = Vector2::new(-before.y, before.x).normalize();
+                let back = Vector2::new(after.y, -after.x).normalize();
+                let beyond = out + back + Vector2::new(back.y - out.y, out.x - back.x);
+                let (last, _) = polygon[polygon.len() - 1];
+                polygon.push((last + out * reach, None));
+                polygon.push((at + beyond.normalize() * (reach + reach), None));
+                polygon.push((polygon[0].0 + back * reach, Some(next)));
+            }
+            for (i, &a) in clip.iter().enumerate() {
+                let b = clip[(i + 1) % clip.len()];
+                polygon = clip_half_plane(&polygon, |p| (b - a).perp_dot(p - a), None);
+            }
+        }
+
+        // a neighbor whose edge was clipped away, or shrinks to a point, is no neighbor
+        let neighbors = polygon
+            .iter()
+            .enumerate()
+            .filter(|&(i, &(p, _))| p != polygon[(i + 1) % polygon.len()].0)
+            .filter_map(|(_, &(_, neighbor))| neighbor)
+            .collect();
+        VoronoiCell {
+            site,
+            polygon: polygon.into_iter().map(|(p, _)| p).collect(),
+            neighbors,
+            unbounded: infinite > 0,
+        }
+    }
+
+    /// The cell of every site with at least one edge, see [`DelaunayMesh::voronoi_cell`].
+    pub fn voronoi_cells<'a>(
+        &'a self,
+        clip: &'a [Point2<S::Real>],
+    ) -> impl Iterator<Item = VoronoiCell<S::Real>> + 'a {
+        self.vertex_stars()
+            .filter_map(move |(_, star)| Some(self.voronoi_cell(*star.first()?, clip)))
+    }
+}
+
+/// A corner of a cell being clipped, with the site across the edge from it to the next corner,
+/// if there is one.
+type Corner<S> = (Point2<S>, Option<VertexEntity>);
+
+/// One pass of Sutherland–Hodgman: the part of the convex `polygon` where `inside` is not
+/// negative. `inside` must be affine, so that crossings can be interpolated. The edge along
+/// the cut is labelled `cut`.
+fn clip_half_plane<S: BaseFloat>(
+    polygon: &[Corner<S>],
+    inside: impl Fn(Point2<S>) -> S,
+    cut: Option<VertexEntity>,
+) -> Vec<Corner<S>> {
+    let mut clipped = Vec::new();
+    for (i, &(p, edge)) in polygon.iter().enumerate() {
+        let (q, _) = polygon[(i + 1) % polygon.len()];
+        let (p_inside, q_inside) = (inside(p), inside(q));
+        if p_inside >= S::zero() {
+            clipped.push((p, edge));
+        }
+        if (p_inside >= S::zero()) != (q_inside >= S::zero()) {
+            let t = p_inside / (p_inside - q_inside);
+            // leaving, the cut follows; entering, the rest of the edge does
+            let label = if p_inside >= S::zero() { cut } else { edge };
+            clipped.push((p + (q - p) * t, label));
+        }
+    }
+    clipped
+}
+
+/// Shoelace formula, positive for counterclockwise polygons.
+fn polygon_area<S: BaseFloat>(polygon: &[Point2<S>]) -> S {
+    let twice_area = polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .fold(S::zero(), |sum, (p, q)| sum + p.x * q.y - q.x * p.y);
+    twice_area / (S::one() + S::one())
+}